@@ -0,0 +1,66 @@
+use std::fs;
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use advent_of_code_2021::runner::default_input_path;
+use advent_of_code_2021::solution::Solution;
+use advent_of_code_2021::*;
+
+/// Benchmarks the generator and each part of the solution separately, using the committed puzzle
+/// input for the day. Days without a committed puzzle input are skipped.
+fn bench_solution<S: Solution>(c: &mut Criterion, solution: S) {
+    let input = match fs::read_to_string(default_input_path(S::DAY)) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+    let parsed = solution.parse(&input).unwrap();
+    let mut group = c.benchmark_group(format!("day_{:02}", S::DAY));
+    // Some days take over a second per run, so keep to the minimum sample size
+    group.sample_size(10);
+    group.bench_function("generator", |b| {
+        b.iter(|| solution.parse(black_box(&input)).unwrap())
+    });
+    group.bench_function("part_1", |b| {
+        b.iter(|| solution.part1(black_box(&parsed)).to_string())
+    });
+    if solution.part2(&parsed).is_some() {
+        group.bench_function("part_2", |b| {
+            b.iter(|| solution.part2(black_box(&parsed)).unwrap().to_string())
+        });
+    }
+    group.finish();
+}
+
+fn bench_all_days(c: &mut Criterion) {
+    bench_solution(c, day_01::Day01);
+    bench_solution(c, day_02::Day02);
+    bench_solution(c, day_03::Day03);
+    bench_solution(c, day_04::Day04);
+    bench_solution(c, day_05::Day05);
+    bench_solution(c, day_06::Day06);
+    bench_solution(c, day_07::Day07);
+    bench_solution(c, day_08::Day08);
+    bench_solution(c, day_09::Day09);
+    bench_solution(c, day_10::Day10);
+    bench_solution(c, day_11::Day11);
+    bench_solution(c, day_12::Day12);
+    bench_solution(c, day_13::Day13);
+    bench_solution(c, day_14::Day14);
+    bench_solution(c, day_15::Day15);
+    bench_solution(c, day_16::Day16);
+    bench_solution(c, day_17::Day17);
+    bench_solution(c, day_18::Day18);
+    bench_solution(c, day_19::Day19);
+    bench_solution(c, day_20::Day20);
+    bench_solution(c, day_21::Day21);
+    bench_solution(c, day_22::Day22);
+    bench_solution(c, day_23::Day23);
+    bench_solution(c, day_24::Day24);
+    bench_solution(c, day_25::Day25);
+}
+
+criterion_group!(benches, bench_all_days);
+criterion_main!(benches);
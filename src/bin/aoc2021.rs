@@ -0,0 +1,477 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::IsTerminal;
+use std::io::Read;
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use advent_of_code_2021::runner::*;
+use advent_of_code_2021::simulation::*;
+use advent_of_code_2021::solution::*;
+use advent_of_code_2021::verify::*;
+use advent_of_code_2021::viewer::*;
+
+const USAGE: &str = "Usage: aoc2021 <day> [--part 1|2] [--input path|-]
+       aoc2021 summary
+       aoc2021 verify [--manifest path]
+       aoc2021 animate <day> (--gif path | --frames dir) [--steps n] [--scale n] [--input path|-]
+       aoc2021 viewer <day> [--interval ms] [--input path]";
+
+/// Number of steps recorded by the animate command if none is given.
+const DEFAULT_ANIMATION_STEPS: usize = 100;
+
+/// Size in pixels of each cell in the frames written by the animate command if none is given.
+const DEFAULT_ANIMATION_SCALE: usize = 4;
+
+/// Time in hundredths of a second that each frame is shown in an animated GIF.
+const GIF_FRAME_DELAY: u16 = 10;
+
+/// Time in milliseconds between steps while the viewer is running if none is given.
+const DEFAULT_VIEWER_INTERVAL: u64 = 100;
+
+/// Action requested through the command line arguments.
+enum Command {
+    /// Run a single day and print the answers.
+    Run(Options),
+    /// Run every day on its default input and print a table of the times taken.
+    Summary,
+    /// Check the answers for every entry in the manifest of expected answers and every example
+    /// fixture.
+    Verify { manifest_path: String },
+    /// Record a day's simulation and write it out as frames.
+    Animate(AnimateOptions),
+    /// Show a day's simulation in the terminal, stepping it as commands are entered.
+    Viewer(ViewerOptions),
+}
+
+/// Where the frames recorded by the animate command are written.
+enum AnimationOutput {
+    /// Animated GIF file.
+    Gif(String),
+    /// Directory of numbered PPM images.
+    Frames(String),
+}
+
+/// Options for the animate command, parsed from the command line arguments.
+struct AnimateOptions {
+    day: u8,
+    steps: usize,
+    scale: usize,
+    input_path: Option<String>,
+    output: AnimationOutput,
+}
+
+/// Options for the viewer command, parsed from the command line arguments.
+struct ViewerOptions {
+    day: u8,
+    interval: Duration,
+    input_path: Option<String>,
+}
+
+/// Options for a single run, parsed from the command line arguments.
+struct Options {
+    day: u8,
+    parts: Vec<u8>,
+    input_path: Option<String>,
+}
+
+fn main() {
+    let command = match parse_args(env::args().skip(1).collect::<Vec<String>>()) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("error: {}\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    let success = match command {
+        Command::Run(options) => run_single_day(&options),
+        Command::Summary => run_summary(),
+        Command::Verify { manifest_path } => run_verify(&manifest_path),
+        Command::Animate(options) => run_animate(&options),
+        Command::Viewer(options) => run_viewer(&options),
+    };
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Runs the day given in the options and prints the answers. Returns false if the run failed.
+fn run_single_day(options: &Options) -> bool {
+    let input = match read_input(options.day, &options.input_path) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return false;
+        }
+    };
+    match run_day(options.day, &input, &options.parts) {
+        Ok(report) => print_report(&report),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Runs every day that has a puzzle input file and prints a table of the times taken. Returns
+/// false if any day failed.
+fn run_summary() -> bool {
+    let mut reports: Vec<DayReport> = vec![];
+    let mut success = true;
+    for solution in registry() {
+        let path = default_input_path(solution.day());
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(_) => {
+                eprintln!("skipping day {}: no input at {}", solution.day(), path);
+                continue;
+            }
+        };
+        match solution.run(&input, &[]) {
+            Ok(report) => reports.push(report),
+            Err(error) => {
+                eprintln!("error: {}", error);
+                success = false;
+            }
+        }
+    }
+    print!("{}", format_summary_table(&reports));
+    return success;
+}
+
+/// Checks the answers in the manifest and for the example fixtures, and prints whether each entry
+/// passed. Returns false if any entry did not pass.
+fn run_verify(manifest_path: &str) -> bool {
+    let expectations = match load_manifest(manifest_path).and_then(|mut expectations| {
+        expectations.extend(discover_fixtures(FIXTURE_DIR)?);
+        Ok(expectations)
+    }) {
+        Ok(expectations) => expectations,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return false;
+        }
+    };
+    let verifications = verify(&expectations);
+    let mut passed = 0;
+    for verification in verifications.iter() {
+        let expectation = &verification.expectation;
+        let status = match &verification.outcome {
+            Outcome::Pass => {
+                passed += 1;
+                String::from("PASS")
+            }
+            Outcome::Fail { actual } => format!(
+                "FAIL (expected {:?}, got {:?})",
+                expectation.expected, actual
+            ),
+            Outcome::Error(message) => format!("ERROR ({})", message),
+        };
+        println!(
+            "Day {} - part {} - {}: {}",
+            expectation.day, expectation.part, expectation.input, status
+        );
+    }
+    println!("{}/{} passed", passed, verifications.len());
+    return passed == verifications.len();
+}
+
+/// Records the simulation for the day given in the options and writes out the frames. Returns
+/// false if the day has no simulation or the frames could not be written.
+fn run_animate(options: &AnimateOptions) -> bool {
+    let mut simulation = match read_input(options.day, &options.input_path)
+        .and_then(|input| simulation_for_day(options.day, &input))
+    {
+        Ok(simulation) => simulation,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return false;
+        }
+    };
+    let mut recorder = Recorder::new();
+    recorder.record(&mut *simulation, options.steps);
+    let result = match &options.output {
+        AnimationOutput::Gif(path) => recorder
+            .write_gif(path, options.scale, GIF_FRAME_DELAY)
+            .map(|_| path.clone()),
+        AnimationOutput::Frames(dir) => recorder
+            .write_ppm_frames(dir, options.scale)
+            .map(|paths| format!("{} ({} frames)", dir, paths.len())),
+    };
+    match result {
+        Ok(written) => println!("Day {} - wrote {}", options.day, written),
+        Err(error) => {
+            eprintln!("error: failed to write animation: {}", error);
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Shows the simulation for the day given in the options in the terminal. Commands are read from
+/// stdin as single key presses if it is a terminal, or otherwise one line at a time. Returns false
+/// if the day has no simulation or the terminal could not be written to.
+fn run_viewer(options: &ViewerOptions) -> bool {
+    let mut simulation = match read_input(options.day, &options.input_path)
+        .and_then(|input| simulation_for_day(options.day, &input))
+    {
+        Ok(simulation) => simulation,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return false;
+        }
+    };
+    // Terminal is restored to line input when the key reader is dropped
+    let key_reader = if io::stdin().is_terminal() {
+        KeyReader::enable()
+    } else {
+        None
+    };
+    let mode = match key_reader {
+        Some(_) => InputMode::Keys,
+        None => InputMode::Lines,
+    };
+    // Read commands on a separate thread, so the simulation can keep running while waiting
+    let (sender, receiver) = mpsc::channel::<ViewerInput>();
+    thread::spawn(move || {
+        if mode == InputMode::Keys {
+            for byte in io::stdin().lock().bytes().map_while(Result::ok) {
+                if sender.send(ViewerInput::Key(byte as char)).is_err() {
+                    break;
+                }
+            }
+        } else {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(ViewerInput::Line(line)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let mut viewer = Viewer::new(&mut *simulation, io::stdout(), options.interval, mode);
+    let result = viewer.run(&receiver);
+    drop(key_reader);
+    if let Err(error) = result {
+        eprintln!("error: failed to draw simulation: {}", error);
+        return false;
+    }
+    return true;
+}
+
+/// Puts the terminal attached to stdin into noncanonical mode while it exists, so that each key
+/// press can be read as soon as it is made. Echo is turned off, and Ctrl-C is read as a key rather
+/// than raising a signal so that the terminal is always restored.
+struct KeyReader {
+    saved_settings: String,
+}
+
+impl KeyReader {
+    /// Switches the terminal to noncanonical mode using `stty`. Returns None if the terminal
+    /// settings could not be read or changed, in which case commands are read line by line.
+    fn enable() -> Option<Self> {
+        let saved = process::Command::new("stty").arg("-g").output().ok()?;
+        if !saved.status.success() {
+            return None;
+        }
+        let saved_settings = String::from_utf8(saved.stdout).ok()?.trim().to_string();
+        let status = process::Command::new("stty")
+            .args(["-icanon", "-echo", "-isig", "min", "1"])
+            .status()
+            .ok()?;
+        if !status.success() {
+            return None;
+        }
+        return Some(Self {
+            saved_settings: saved_settings,
+        });
+    }
+}
+
+impl Drop for KeyReader {
+    fn drop(&mut self) {
+        let _ = process::Command::new("stty")
+            .arg(&self.saved_settings)
+            .status();
+    }
+}
+
+/// Parses the command line arguments (excluding the program name).
+fn parse_args(args: Vec<String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let first = args.next().ok_or_else(|| String::from("missing day"))?;
+    if first == "summary" {
+        if let Some(arg) = args.next() {
+            return Err(format!("unexpected argument: {}", arg));
+        }
+        return Ok(Command::Summary);
+    }
+    if first == "verify" {
+        let mut manifest_path = String::from(MANIFEST_PATH);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--manifest" | "-m" => {
+                    manifest_path = args
+                        .next()
+                        .ok_or_else(|| String::from("--manifest requires a path"))?;
+                }
+                _ => return Err(format!("unexpected argument: {}", arg)),
+            }
+        }
+        return Ok(Command::Verify {
+            manifest_path: manifest_path,
+        });
+    }
+    if first == "animate" {
+        return parse_animate_args(args);
+    }
+    if first == "viewer" {
+        return parse_viewer_args(args);
+    }
+    let day = parse_day(&first)?;
+    let mut options = Options {
+        day: day,
+        parts: vec![],
+        input_path: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" | "-p" => {
+                let part = args
+                    .next()
+                    .and_then(|p| p.parse::<u8>().ok())
+                    .filter(|p| *p == 1 || *p == 2)
+                    .ok_or_else(|| String::from("--part must be 1 or 2"))?;
+                options.parts.push(part);
+            }
+            "--input" | "-i" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| String::from("--input requires a path"))?;
+                options.input_path = Some(path);
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    return Ok(Command::Run(options));
+}
+
+/// Parses the arguments following the animate command.
+fn parse_animate_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let day = parse_day(&args.next().ok_or_else(|| String::from("missing day"))?)?;
+    let mut steps = DEFAULT_ANIMATION_STEPS;
+    let mut scale = DEFAULT_ANIMATION_SCALE;
+    let mut input_path: Option<String> = None;
+    let mut output: Option<AnimationOutput> = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{} requires a value", arg))
+        };
+        match arg.as_str() {
+            "--gif" => output = Some(AnimationOutput::Gif(value()?)),
+            "--frames" => output = Some(AnimationOutput::Frames(value()?)),
+            "--input" | "-i" => input_path = Some(value()?),
+            "--steps" | "-n" => {
+                steps = value()?
+                    .parse::<usize>()
+                    .map_err(|_| String::from("--steps must be a non-negative number"))?;
+            }
+            "--scale" => {
+                scale = value()?
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|scale| *scale > 0)
+                    .ok_or_else(|| String::from("--scale must be a positive number"))?;
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    let output = output.ok_or_else(|| String::from("animate requires --gif or --frames"))?;
+    return Ok(Command::Animate(AnimateOptions {
+        day: day,
+        steps: steps,
+        scale: scale,
+        input_path: input_path,
+        output: output,
+    }));
+}
+
+/// Parses the arguments following the viewer command.
+fn parse_viewer_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let day = parse_day(&args.next().ok_or_else(|| String::from("missing day"))?)?;
+    let mut options = ViewerOptions {
+        day: day,
+        interval: Duration::from_millis(DEFAULT_VIEWER_INTERVAL),
+        input_path: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--interval" => {
+                let interval = args
+                    .next()
+                    .and_then(|ms| ms.parse::<u64>().ok())
+                    .ok_or_else(|| String::from("--interval must be a number of milliseconds"))?;
+                options.interval = Duration::from_millis(interval);
+            }
+            "--input" | "-i" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| String::from("--input requires a path"))?;
+                // Commands are read from stdin, so it cannot also hold the puzzle input
+                if path == "-" {
+                    return Err(String::from("viewer cannot read its input from stdin"));
+                }
+                options.input_path = Some(path);
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    return Ok(Command::Viewer(options));
+}
+
+/// Parses the day given on the command line.
+fn parse_day(arg: &str) -> Result<u8, String> {
+    return arg
+        .parse::<u8>()
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+        .ok_or_else(|| String::from("day must be a number from 1 to 25"));
+}
+
+/// Reads the puzzle input from the given file or stdin, or from the default input file for the day.
+fn read_input(day: u8, input_path: &Option<String>) -> Result<String, String> {
+    let path = input_path
+        .clone()
+        .unwrap_or_else(|| default_input_path(day));
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("failed to read stdin: {}", e))?;
+        return Ok(input);
+    }
+    return fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {}", path, e));
+}
+
+/// Prints the answers and times taken from the report.
+fn print_report(report: &DayReport) {
+    println!(
+        "Day {} - generator: {}ms",
+        report.day,
+        format_millis(report.parse_time)
+    );
+    for part in report.parts.iter() {
+        println!(
+            "Day {} - part {}: {} ({}ms)",
+            report.day,
+            part.part,
+            part.answer,
+            format_millis(part.time)
+        );
+    }
+}
@@ -1,82 +1,90 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use super::utils::parse;
+
 #[aoc_generator(day1)]
-fn parse_input(input: &str) -> Vec<u64> {
-    let mut output: Vec<u64> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let value = line.parse::<u64>().unwrap();
-        output.push(value);
-    }
-    return output;
+fn parse_input(input: &str) -> Result<Vec<u64>, parse::ParseError> {
+    return read_depths(input.as_bytes()).collect();
+}
+
+/// Reads the sonar sweep depths from the reader, one per line. The input is read one line at a
+/// time, so large inputs do not need to be loaded into memory.
+pub fn read_depths<R: BufRead>(reader: R) -> impl Iterator<Item = Result<u64, parse::ParseError>> {
+    return parse::read_lines(reader)
+        .map(|line| line.and_then(|(line_num, line)| parse::value::<u64>(line_num, &line)));
 }
 
 #[aoc(day1, part1)]
 fn solve_part_1(values: &Vec<u64>) -> u64 {
-    // Initiate variables to track increase count and previous value
-    let mut count: u64 = 0;
-    for i in 1..values.len() {
-        if values[i] > values[i - 1] {
-            count += 1;
-        }
-    }
-    return count;
+    return count_increases(values.iter().copied(), 1) as u64;
 }
 
 #[aoc(day1, part2)]
 fn solve_part_2(values: &Vec<u64>) -> u64 {
-    // Initiate variable to track count and previous window value
-    let mut count: u64 = 0;
-    let mut previous: u64 = 0;
-    let mut started = false;
-    for i in 2..values.len() {
-        let window_value = calculate_window_value(values, i);
-        // Handle start case
-        if !started {
-            started = true;
-            previous = window_value;
-            continue;
-        }
-        // Check if current window value is greater than previous window value, then slide along
-        if window_value > previous {
-            count += 1;
-        }
-        previous = window_value;
-    }
-    return count;
+    return count_increases(values.iter().copied(), 3) as u64;
 }
 
-/// Calculates the total value of the three-value window ending at the specified index.
-/// 
-/// Assumption: end_index is greater than or equal to 2.
-fn calculate_window_value(values: &Vec<u64>, end_index: usize) -> u64 {
-    if end_index < 2 {
-        panic!("Day 1: bad end index value for sliding window calculation!");
-    }
-    let mut window_value: u64 = 0;
-    for i in 0..3 {
-        window_value += values[end_index - i];
-    }
-    return window_value;
+/// Counts the number of times the sum of a sliding window of the given size increases from one
+/// step to the next.
+pub fn count_increases<I: IntoIterator<Item = u64>>(values: I, window: usize) -> usize {
+    return increase_positions(values, window).count();
 }
 
+/// Finds where the sum of a sliding window of the given size increases from one step to the next.
+/// Neighbouring windows share all but their first and last values, so the sum increases exactly
+/// when `a[i + window] > a[i]` and only the last `window` values need to be held. Yields the
+/// index of the last value in each window that is larger than the one before it, reading the
+/// values only as far as needed for each position.
+pub fn increase_positions<I: IntoIterator<Item = u64>>(
+    values: I,
+    window: usize,
+) -> impl Iterator<Item = usize> {
+    let recent: VecDeque<u64> = VecDeque::with_capacity(window + 1);
+    return values
+        .into_iter()
+        .enumerate()
+        .scan(recent, move |recent, (i, value)| {
+            recent.push_back(value);
+            if recent.len() <= window {
+                return Some(None);
+            }
+            // Compare against the value that drops out of the window as the new value enters it
+            let oldest = recent.pop_front().unwrap();
+            return Some(Some(i).filter(|_| value > oldest));
+        })
+        .flatten();
+}
+
+day_solution!(Day01, 1, Vec<u64>);
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs::*;
 
     #[test]
-    fn test_d01_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day1.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(1266, result);
+    fn test_d01_count_increases() {
+        let values = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        assert_eq!(7, count_increases(values.iter().copied(), 1));
+        assert_eq!(
+            vec![1, 2, 3, 5, 6, 7, 9],
+            increase_positions(values.iter().copied(), 1).collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            vec![3, 6, 7, 8, 9],
+            increase_positions(values.iter().copied(), 3).collect::<Vec<usize>>()
+        );
+        assert_eq!(0, count_increases(values.iter().copied(), 10));
+        assert_eq!(0, count_increases(values.iter().copied(), 0));
     }
 
     #[test]
-    fn test_d01_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day1.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(1217, result);
+    fn test_d01_count_increases_streaming() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let depths = read_depths(input.as_bytes()).map(|depth| depth.unwrap());
+        assert_eq!(5, count_increases(depths, 3));
+        // Positions are produced lazily, so an endless stream can be searched
+        let mut first = increase_positions((0..).map(|i| i % 4), 2);
+        assert_eq!(Some(2), first.next());
     }
 }
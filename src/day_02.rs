@@ -1,105 +1,209 @@
-use regex::Regex;
+use std::io::BufRead;
 
-enum Command {
-    Forward{units: i64},
-    Down{units: i64},
-    Up{units: i64}
+use super::utils::map::Point2D;
+use super::utils::parse;
+
+/// Single command from the planned course of the submarine.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    Forward { units: i64 },
+    Down { units: i64 },
+    Up { units: i64 },
 }
 
-#[aoc_generator(day2)]
-fn parse_input(input: &str) -> Vec<Command> {
-    let mut output: Vec<Command> = vec![];
-    let forward_regex = Regex::new(r"^forward (\d+)$").unwrap();
-    let down_regex = Regex::new(r"^down (\d+)$").unwrap();
-    let up_regex = Regex::new(r"^up (\d+)$").unwrap();
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+/// Common behaviour for submarines that are steered by the course commands. Positions have the
+/// horizontal position as the x-coordinate and the depth as the y-coordinate.
+pub trait Submarine {
+    /// Applies the command to the submarine, recording its new position in the trace.
+    fn apply(&mut self, command: &Command);
+
+    /// Gets the positions of the submarine, starting from its initial position and followed by its
+    /// position after each command applied.
+    fn trace(&self) -> &[Point2D];
+
+    /// Gets the current position of the submarine.
+    fn position(&self) -> Point2D {
+        return *self.trace().last().unwrap();
+    }
+
+    /// Gets the greatest depth reached by the submarine.
+    fn max_depth(&self) -> i64 {
+        return self.trace().iter().map(|p| p.get_y()).max().unwrap();
+    }
+
+    /// Gets the total straight-line distance travelled by the submarine.
+    fn path_length(&self) -> f64 {
+        return self
+            .trace()
+            .windows(2)
+            .map(|pair| {
+                let delta = pair[1] - pair[0];
+                ((delta.get_x().pow(2) + delta.get_y().pow(2)) as f64).sqrt()
+            })
+            .sum::<f64>();
+    }
+}
+
+/// Submarine where "down" and "up" change the depth directly.
+pub struct SimpleSubmarine {
+    trace: Vec<Point2D>,
+}
+
+impl SimpleSubmarine {
+    /// Creates a new submarine at horizontal position 0 and depth 0.
+    pub fn new() -> Self {
+        Self {
+            trace: vec![Point2D::new(0, 0)],
         }
-        // Try to match forward regex
-        if forward_regex.is_match(line) {
-            let captures = forward_regex.captures(line).unwrap();
-            let units_val = captures[1].parse::<i64>().unwrap();
-            output.push(Command::Forward{units: units_val});
-        // Try to match down regex
-        } else if down_regex.is_match(line) {
-            let captures = down_regex.captures(line).unwrap();
-            let units_val = captures[1].parse::<i64>().unwrap();
-            output.push(Command::Down{units: units_val});
-        // Try to match up regex
-        } else if up_regex.is_match(line) {
-            let captures = up_regex.captures(line).unwrap();
-            let units_val = captures[1].parse::<i64>().unwrap();
-            output.push(Command::Up{units: units_val});
-        // Line does not match an expected format - panic!
-        } else {
-            panic!("Day 2 - input line does not match an expected format!");
+    }
+}
+
+impl Default for SimpleSubmarine {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Submarine for SimpleSubmarine {
+    fn apply(&mut self, command: &Command) {
+        let delta = match command {
+            Command::Forward { units } => Point2D::new(*units, 0),
+            Command::Down { units } => Point2D::new(0, *units),
+            Command::Up { units } => Point2D::new(0, -units),
+        };
+        self.trace.push(self.position() + delta);
+    }
+
+    fn trace(&self) -> &[Point2D] {
+        return &self.trace;
+    }
+}
+
+/// Submarine where "down" and "up" change the aim, and moving forward changes the depth by the aim
+/// multiplied by the units moved.
+pub struct AimedSubmarine {
+    trace: Vec<Point2D>,
+    aim: i64,
+}
+
+impl AimedSubmarine {
+    /// Creates a new submarine at horizontal position 0 and depth 0, with an aim of 0.
+    pub fn new() -> Self {
+        Self {
+            trace: vec![Point2D::new(0, 0)],
+            aim: 0,
         }
     }
-    return output;
 }
 
-#[aoc(day2, part1)]
-fn solve_part_1(commands: &Vec<Command>) -> i64 {
-    // Submarine starts at depth 0 and horizonal 0
-    let mut x_pos: i64 = 0;
-    let mut y_pos: i64 = 0;
-    for command in commands {
-        match command {
-            Command::Forward{units} => {
-                x_pos += units;
-            },
-            Command::Down{units} => {
-                y_pos += units;
-            },
-            Command::Up{units} => {
-                y_pos -= units;
+impl Default for AimedSubmarine {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Submarine for AimedSubmarine {
+    fn apply(&mut self, command: &Command) {
+        let delta = match command {
+            Command::Forward { units } => Point2D::new(*units, self.aim * units),
+            Command::Down { units } => {
+                self.aim += units;
+                Point2D::new(0, 0)
             }
-        }
+            Command::Up { units } => {
+                self.aim -= units;
+                Point2D::new(0, 0)
+            }
+        };
+        self.trace.push(self.position() + delta);
     }
-    return x_pos * y_pos;
+
+    fn trace(&self) -> &[Point2D] {
+        return &self.trace;
+    }
+}
+
+#[aoc_generator(day2)]
+fn parse_input(input: &str) -> Result<Vec<Command>, parse::ParseError> {
+    return read_commands(input.as_bytes()).collect();
+}
+
+/// Reads the course commands from the reader, one per line. The input is read one line at a time,
+/// so large inputs do not need to be loaded into memory.
+pub fn read_commands<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Command, parse::ParseError>> {
+    return parse::read_lines(reader)
+        .map(|line| line.and_then(|(line_num, line)| parse_command(line_num, &line)));
+}
+
+/// Parses a single course command from the given line.
+fn parse_command(line_num: usize, line: &str) -> Result<Command, parse::ParseError> {
+    let (name, units) = line.split_once(' ').ok_or_else(|| {
+        parse::ParseError::new(line_num, &format!("expected command and units: {}", line))
+    })?;
+    let units = parse::value::<i64>(line_num, units)?;
+    let command = match name {
+        "forward" => Command::Forward { units: units },
+        "down" => Command::Down { units: units },
+        "up" => Command::Up { units: units },
+        _ => {
+            return Err(parse::ParseError::new(
+                line_num,
+                &format!("unknown command: {}", name),
+            ))
+        }
+    };
+    return Ok(command);
+}
+
+#[aoc(day2, part1)]
+fn solve_part_1(commands: &Vec<Command>) -> i64 {
+    let position = run_course(SimpleSubmarine::new(), commands).position();
+    return position.get_x() * position.get_y();
 }
 
 #[aoc(day2, part2)]
 fn solve_part_2(commands: &Vec<Command>) -> i64 {
-    // Submarine starts with depth, horizonal and aim of 0
-    let mut x_pos: i64 = 0;
-    let mut y_pos: i64 = 0;
-    let mut aim: i64 = 0;
+    let position = run_course(AimedSubmarine::new(), commands).position();
+    return position.get_x() * position.get_y();
+}
+
+/// Applies each of the commands in turn to the submarine.
+pub fn run_course<S: Submarine>(mut submarine: S, commands: &[Command]) -> S {
     for command in commands {
-        match command {
-            Command::Forward{units} => {
-                x_pos += units;
-                y_pos += aim * units;
-            },
-            Command::Down{units} => {
-                aim += units;
-            },
-            Command::Up{units} => {
-                aim -= units;
-            }
-        }
+        submarine.apply(command);
     }
-    return x_pos * y_pos;
+    return submarine;
 }
 
+day_solution!(Day02, 2, Vec<Command>);
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs::*;
+
+    const EXAMPLE_COURSE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
 
     #[test]
-    fn test_d02_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day2.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(1524750, result);
+    fn test_d02_submarine_trace() {
+        let commands = parse_input(EXAMPLE_COURSE).unwrap();
+        let simple = run_course(SimpleSubmarine::new(), &commands);
+        assert_eq!(Point2D::new(15, 10), simple.position());
+        assert_eq!(7, simple.trace().len());
+        assert_eq!(10, simple.max_depth());
+        assert_eq!(31.0, simple.path_length());
+        let aimed = run_course(AimedSubmarine::new(), &commands);
+        assert_eq!(Point2D::new(15, 60), aimed.position());
+        assert_eq!(60, aimed.max_depth());
+        assert_eq!(Point2D::new(13, 40), aimed.trace()[3]);
     }
 
     #[test]
-    fn test_d02_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day2.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(1592426537, result);
+    fn test_d02_unknown_command() {
+        let error = parse_input("forward 5\n\nbackward 2\n").unwrap_err();
+        assert_eq!(3, error.line);
+        assert_eq!("unknown command: backward", error.message);
+        assert!(parse_input("forward x\n").is_err());
     }
 }
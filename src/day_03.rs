@@ -1,17 +1,10 @@
 use std::collections::HashMap;
 
+use super::utils::parse;
+
 #[aoc_generator(day3)]
-fn parse_input(input: &str) -> Vec<Vec<u8>> {
-    let mut output: Vec<Vec<u8>> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let vals = line.chars().map(|x| x.to_digit(10).unwrap() as u8).collect::<Vec<u8>>();
-        output.push(vals);
-    }
-    return output;
+fn parse_input(input: &str) -> Result<Vec<Vec<u8>>, parse::ParseError> {
+    return parse::digit_grid::<u8>(input);
 }
 
 #[aoc(day3, part1)]
@@ -153,22 +146,4 @@ fn solve_part_2(values: &Vec<Vec<u8>>) -> u64 {
     return o2_generator_rating * co2_scrubber_rating;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d03_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day3.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(3687446, result);
-    }
-
-    #[test]
-    fn test_d03_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day3.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(4406844, result);
-    }
-}
+day_solution!(Day03, 3, Vec<Vec<u8>>);
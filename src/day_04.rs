@@ -1,47 +1,37 @@
 use std::collections::HashSet;
 
+use super::utils::parse;
+
 #[aoc_generator(day4)]
-fn parse_input(input: &str) -> (Vec<u64>, Vec<Vec<Vec<(u64, bool)>>>) {
+fn parse_input(input: &str) -> Result<(Vec<u64>, Vec<Vec<Vec<(u64, bool)>>>), parse::ParseError> {
     // Values on each board are stored in 2D array with bool to represent marking state
     let mut boards: Vec<Vec<Vec<(u64, bool)>>> = vec![];
-    let mut lines = input.lines();
+    let blocks = parse::blocks(input);
     // Get bingo numbers from first line
-    let bingo_numbers = lines
-        .next()
-        .unwrap()
-        .trim()
-        .split(",")
-        .map(|x| x.parse::<u64>().unwrap())
-        .collect::<Vec<u64>>();
-    // Keep reading boards until end of input reached
-    loop {
-        // Keep reading lines until either non-empty line or end of input reached
-        let line = lines.next();
-        if line.is_none() {
-            break;
-        } else if line.unwrap().is_empty() {
-            continue;
+    let (line_num, line) = *blocks
+        .first()
+        .and_then(|block| block.first())
+        .ok_or_else(|| parse::ParseError::new(1, "input is empty"))?;
+    let bingo_numbers = parse::separated::<u64>(line_num, line, ",")?;
+    // Read each remaining block into a new bingo board - each tile represented by value and mark state
+    for block in blocks.iter().skip(1) {
+        if block.len() != 5 {
+            return Err(parse::ParseError::new(
+                block[0].0,
+                &format!("expected 5 rows in bingo board, got {}", block.len()),
+            ));
         }
-        // Read lines into a new bingo board - each tile represented by value and mark state
         let mut new_board: Vec<Vec<(u64, bool)>> = vec![];
-        for i in 0..5 {
-            let raw_line = {
-                if i == 0 {
-                    line.unwrap()
-                } else {
-                    lines.next().unwrap()
-                }
-            };
-            let new_line_values = raw_line
-                .trim()
-                .split_ascii_whitespace()
-                .map(|x| (x.parse::<u64>().unwrap(), false))
+        for (line_num, line) in block {
+            let new_line_values = parse::whitespace_separated::<u64>(*line_num, line)?
+                .into_iter()
+                .map(|x| (x, false))
                 .collect::<Vec<(u64, bool)>>();
             new_board.push(new_line_values);
         }
         boards.push(new_board);
     }
-    return (bingo_numbers, boards);
+    return Ok((bingo_numbers, boards));
 }
 
 #[aoc(day4, part1)]
@@ -144,22 +134,4 @@ fn calculate_board_final_score(final_num: u64, board: &Vec<Vec<(u64, bool)>>) ->
     return unmarked_sum * final_num;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d04_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day4.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(41668, result);
-    }
-
-    #[test]
-    fn test_d04_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day4.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(10478, result);
-    }
-}
+day_solution!(Day04, 4, (Vec<u64>, Vec<Vec<Vec<(u64, bool)>>>));
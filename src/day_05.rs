@@ -1,29 +1,31 @@
 use std::collections::HashSet;
-
-use regex::Regex;
+use std::io::BufRead;
 
 use super::utils::map::Point2D;
+use super::utils::parse;
 
 #[aoc_generator(day5)]
-fn parse_input(input: &str) -> Vec<(Point2D, Point2D)> {
-    let mut output: Vec<(Point2D, Point2D)> = vec![];
-    let line_regex = Regex::new(r"^(\d+),(\d+) -> (\d+),(\d+)$").unwrap();
-    for line in input.lines() {
-        // Ignore empty lines
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let captures = line_regex.captures(line).unwrap();
-        let x1 = captures[1].parse::<i64>().unwrap();
-        let y1 = captures[2].parse::<i64>().unwrap();
-        let x2 = captures[3].parse::<i64>().unwrap();
-        let y2 = captures[4].parse::<i64>().unwrap();
-        let point_1 = Point2D::new(x1, y1);
-        let point_2 = Point2D::new(x2, y2);
-        output.push((point_1, point_2));
-    }
-    return output;
+fn parse_input(input: &str) -> Result<Vec<(Point2D, Point2D)>, parse::ParseError> {
+    return read_vent_lines(input.as_bytes()).collect();
+}
+
+/// Reads the start and end points of the hydrothermal vent lines from the reader, one per line.
+/// The input is read one line at a time, so large inputs do not need to be loaded into memory.
+pub fn read_vent_lines<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<(Point2D, Point2D), parse::ParseError>> {
+    return parse::read_lines(reader)
+        .map(|line| line.and_then(|(line_num, line)| parse_vent_line(line_num, &line)));
+}
+
+/// Parses the start and end points of a single vent line, given as "x1,y1 -> x2,y2".
+fn parse_vent_line(line_num: usize, line: &str) -> Result<(Point2D, Point2D), parse::ParseError> {
+    let (start, end) = line
+        .split_once(" -> ")
+        .ok_or_else(|| parse::ParseError::new(line_num, &format!("bad vent line: {}", line)))?;
+    let point_1 = parse::value::<Point2D>(line_num, start)?;
+    let point_2 = parse::value::<Point2D>(line_num, end)?;
+    return Ok((point_1, point_2));
 }
 
 #[aoc(day5, part1)]
@@ -61,35 +63,17 @@ fn mark_points_on_map(
     overlap: &mut HashSet<Point2D>,
 ) {
     // Calculate the co-ordinate deltas so we know what direction the line is pointing
-    let delta_x = (point_2.get_x() - point_1.get_x()).signum();
-    let delta_y = (point_2.get_y() - point_1.get_y()).signum();
-    let mut point = point_1.clone();
-    // Keep adding points until we get to the end point
-    while point.get_x() != point_2.get_x() + delta_x || point.get_y() != point_2.get_y() + delta_y {
+    let diff = *point_2 - *point_1;
+    let delta = Point2D::new(diff.get_x().signum(), diff.get_y().signum());
+    let mut point = *point_1;
+    // Keep adding points until we get past the end point
+    while point != *point_2 + delta {
         let is_new_point = observed.insert(point);
         if !is_new_point {
             overlap.insert(point);
         }
-        point.move_point(delta_x, delta_y);
+        point = point + delta;
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d05_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day5.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(6007, result);
-    }
-
-    #[test]
-    fn test_d05_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day5.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(19349, result);
-    }
-}
+day_solution!(Day05, 5, Vec<(Point2D, Point2D)>);
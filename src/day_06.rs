@@ -1,14 +1,77 @@
 use std::collections::HashMap;
 
+use super::simulation::Simulation;
+use super::utils::parse;
+use super::utils::render::*;
+
+/// Number of cells in the tallest bar of the population chart drawn by the simulation.
+const CHART_HEIGHT: usize = 20;
+
+/// Lanternfish population that advances by one day on each step of the simulation.
+pub struct LanternfishSimulation {
+    fish: HashMap<u64, u64>,
+}
+
+impl LanternfishSimulation {
+    /// Creates a new simulation from the initial fish timers given in the puzzle input.
+    pub fn from_input(input: &str) -> Result<Self, parse::ParseError> {
+        return Ok(Self {
+            fish: parse_input(input)?,
+        });
+    }
+
+    /// Gets the total number of fish in the population.
+    pub fn population(&self) -> u64 {
+        return self.fish.values().sum();
+    }
+}
+
+impl Simulation for LanternfishSimulation {
+    fn step(&mut self) {
+        self.fish = conduct_single_turn(&self.fish);
+    }
+
+    /// Draws a bar chart of the number of fish with each timer value from 0 to 8, scaled so that
+    /// the largest group fills the height of the chart.
+    fn snapshot(&self) -> GridImage {
+        let counts = (0..=8)
+            .map(|timer| *self.fish.get(&timer).unwrap_or(&0))
+            .collect::<Vec<u64>>();
+        let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+        let bar_heights = counts
+            .iter()
+            .map(|count| {
+                (*count as u128 * CHART_HEIGHT as u128).div_ceil(max_count as u128) as usize
+            })
+            .collect::<Vec<usize>>();
+        let palette = Palette::gradient(Colour::new(255, 64, 0), Colour::new(0, 128, 255), 9);
+        // Each cell holds the timer value of the bar covering it, if any
+        let grid = (0..CHART_HEIGHT)
+            .map(|y| {
+                (0..9)
+                    .map(|timer| {
+                        Some(timer as u64).filter(|_| CHART_HEIGHT - y <= bar_heights[timer])
+                    })
+                    .collect::<Vec<Option<u64>>>()
+            })
+            .collect::<Vec<Vec<Option<u64>>>>();
+        return GridImage::from_grid(&grid, |timer| match timer {
+            Some(timer) => palette.colour(*timer),
+            None => Colour::BLACK,
+        })
+        .unwrap();
+    }
+}
+
 #[aoc_generator(day6)]
-fn parse_input(input: &str) -> HashMap<u64, u64> {
+fn parse_input(input: &str) -> Result<HashMap<u64, u64>, parse::ParseError> {
     // Track remaining timer as key and number of occurrences as value
     let mut output: HashMap<u64, u64> = HashMap::new();
-    let initial_ages = input.lines().next().unwrap().split(",").map(|x| x.parse::<u64>().unwrap());
+    let initial_ages = parse::comma_separated::<u64>(input)?;
     for age in initial_ages {
         *output.entry(age).or_insert(0) += 1;
     }
-    return output;
+    return Ok(output);
 }
 
 #[aoc(day6, part1)]
@@ -62,22 +125,25 @@ fn conduct_single_turn(current_fish: &HashMap<u64, u64>) -> HashMap<u64, u64> {
     return new_fish;
 }
 
+day_solution!(Day06, 6, HashMap<u64, u64>);
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs::*;
 
     #[test]
-    fn test_d06_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day6.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(366057, result);
-    }
-
-    #[test]
-    fn test_d06_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day6.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(1653559299811, result);
+    fn test_d06_simulation_snapshot() {
+        let mut simulation = LanternfishSimulation::from_input("3,4,3,1,2\n").unwrap();
+        for _ in 0..18 {
+            simulation.step();
+        }
+        assert_eq!(26, simulation.population());
+        // Timers 1 and 6 have the most fish (5), so their bars fill the chart
+        let image = simulation.snapshot();
+        assert_eq!((9, CHART_HEIGHT), (image.width(), image.height()));
+        assert_ne!(Some(Colour::BLACK), image.get(6, 0));
+        assert_eq!(image.get(6, 0), image.get(6, CHART_HEIGHT - 1));
+        assert_eq!(Some(Colour::BLACK), image.get(0, 0));
+        assert_ne!(Some(Colour::BLACK), image.get(0, CHART_HEIGHT - 1));
     }
 }
@@ -1,15 +1,10 @@
+use super::utils::parse;
+
 #[aoc_generator(day7)]
-fn parse_input(input: &str) -> Vec<i64> {
-    let mut output = input
-        .lines()
-        .next()
-        .unwrap()
-        .trim()
-        .split(",")
-        .map(|x| x.parse::<i64>().unwrap())
-        .collect::<Vec<i64>>();
+fn parse_input(input: &str) -> Result<Vec<i64>, parse::ParseError> {
+    let mut output = parse::comma_separated::<i64>(input)?;
     output.sort();
-    return output;
+    return Ok(output);
 }
 
 #[aoc(day7, part1)]
@@ -47,22 +42,4 @@ fn solve_part_2(initial_pos: &Vec<i64>) -> i64 {
     return min_fuel;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d07_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day7.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(347011, result);
-    }
-
-    #[test]
-    fn test_d07_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day7.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(98363777, result);
-    }
-}
+day_solution!(Day07, 7, Vec<i64>);
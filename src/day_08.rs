@@ -3,33 +3,27 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
+use super::utils::parse;
+
 #[aoc_generator(day8)]
-fn parse_input(input: &str) -> Vec<(Vec<String>, Vec<String>)> {
+fn parse_input(input: &str) -> Result<Vec<(Vec<String>, Vec<String>)>, parse::ParseError> {
     let mut output: Vec<(Vec<String>, Vec<String>)> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+    for (line_num, line) in parse::lines(input) {
         // Separate the unique signal patterns and output values from input line
-        let mut split = line.split(" | ");
-        let signal_patterns = split
-            .next()
-            .unwrap()
-            .trim()
+        let (signal_patterns, output_values) = line.split_once(" | ").ok_or_else(|| {
+            parse::ParseError::new(line_num, "expected signal patterns and output values")
+        })?;
+        let signal_patterns = signal_patterns
             .split_whitespace()
             .map(|x| x.chars().sorted().collect::<String>())
             .collect::<Vec<String>>();
-        let output_values = split
-            .next()
-            .unwrap()
-            .trim()
+        let output_values = output_values
             .split_whitespace()
             .map(|x| x.chars().sorted().collect::<String>())
             .collect::<Vec<String>>();
         output.push((signal_patterns, output_values));
     }
-    return output;
+    return Ok(output);
 }
 
 #[aoc(day8, part1)]
@@ -334,22 +328,4 @@ fn initialise_wire_possibles_map() -> HashMap<char, Vec<HashSet<char>>> {
     return possibles;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d08_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day8.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(532, result);
-    }
-
-    #[test]
-    fn test_d08_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day8.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(1011284, result);
-    }
-}
+day_solution!(Day08, 8, Vec<(Vec<String>, Vec<String>)>);
@@ -1,20 +1,11 @@
 use std::collections::HashSet;
 
+use super::utils::parse;
+use super::utils::render::*;
+
 #[aoc_generator(day9)]
-fn parse_input(input: &str) -> Vec<Vec<u64>> {
-    let mut height_map: Vec<Vec<u64>> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let map_line = line
-            .chars()
-            .map(|x| x.to_digit(10).unwrap() as u64)
-            .collect::<Vec<u64>>();
-        height_map.push(map_line);
-    }
-    return height_map;
+fn parse_input(input: &str) -> Result<Vec<Vec<u64>>, parse::ParseError> {
+    return parse::digit_grid::<u64>(input);
 }
 
 #[aoc(day9, part1)]
@@ -48,6 +39,27 @@ fn solve_part_1(height_map: &Vec<Vec<u64>>) -> u64 {
 
 #[aoc(day9, part2)]
 fn solve_part_2(height_map: &Vec<Vec<u64>>) -> usize {
+    // Calculate the product of the size of the three largest basins
+    let basins = find_basins(height_map);
+    let mut basin_sizes = basins.iter().map(|x| x.len()).collect::<Vec<usize>>();
+    basin_sizes.sort();
+    let mut product = 1;
+    for i in (basin_sizes.len() - 3)..=(basin_sizes.len() - 1) {
+        product *= basin_sizes[i];
+    }
+    return product;
+}
+
+/// Creates an overlay highlighting the given number of largest basins in the height map, for
+/// drawing over an image of the heights.
+pub fn basin_overlay(height_map: &Vec<Vec<u64>>, count: usize, colour: Colour) -> Overlay {
+    let mut basins = find_basins(height_map);
+    basins.sort_by_key(|basin| std::cmp::Reverse(basin.len()));
+    return Overlay::new(basins.into_iter().take(count).flatten(), colour);
+}
+
+/// Finds the basins in the height map - the areas separated from each other by points of height 9.
+fn find_basins(height_map: &Vec<Vec<u64>>) -> Vec<HashSet<(usize, usize)>> {
     // Record all the basins discovered and all points so far included in a basin
     let mut basins: Vec<HashSet<(usize, usize)>> = vec![];
     let mut observed: HashSet<(usize, usize)> = HashSet::new();
@@ -66,14 +78,7 @@ fn solve_part_2(height_map: &Vec<Vec<u64>>) -> usize {
             }
         }
     }
-    // Calculate the product of the size of the three largest basins
-    let mut basin_sizes = basins.iter().map(|x| x.len()).collect::<Vec<usize>>();
-    basin_sizes.sort();
-    let mut product = 1;
-    for i in (basin_sizes.len() - 3)..=(basin_sizes.len() - 1) {
-        product *= basin_sizes[i];
-    }
-    return product;
+    return basins;
 }
 
 /// Adds points to the current basin using breadth-first recursion.
@@ -102,22 +107,30 @@ fn add_to_basin_recursive(
     }
 }
 
+day_solution!(Day09, 9, Vec<Vec<u64>>);
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::fs::*;
 
     #[test]
-    fn test_d09_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day9.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(558, result);
-    }
-
-    #[test]
-    fn test_d09_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day9.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(882942, result);
+    fn test_d09_render_basins() {
+        let input = read_to_string("./input/2021/test/day_09_test_001.txt").unwrap();
+        let height_map = parse_input(&input).unwrap();
+        let mut image = GridImage::from_values(&height_map, &Palette::greyscale(10)).unwrap();
+        image.apply_overlay(&basin_overlay(&height_map, 3, Colour::YELLOW));
+        let highlighted = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| image.get(*x, *y) == Some(Colour::YELLOW))
+            .count();
+        // Three largest basins in the example have sizes 14, 9 and 9
+        assert_eq!(14 + 9 + 9, highlighted);
+        assert_eq!(Some(Colour::YELLOW), image.get(9, 0));
+        assert_eq!(Some(Colour::YELLOW), image.get(2, 2));
+        assert_eq!(Some(Colour::YELLOW), image.get(9, 4));
+        // Top-left basin of size 3 is not among the largest, and peaks are left as they are
+        assert_eq!(Some(Palette::greyscale(10).colour(2)), image.get(0, 0));
+        assert_eq!(Some(Colour::WHITE), image.get(2, 0));
     }
 }
@@ -1,16 +1,12 @@
 use std::collections::VecDeque;
 
+use super::utils::parse;
+
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Vec<String> {
-    let mut output: Vec<String> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        output.push(line.to_string());
-    }
-    return output;
+fn parse_input(input: &str) -> Result<Vec<String>, parse::ParseError> {
+    return Ok(parse::lines(input)
+        .map(|(_, line)| line.to_string())
+        .collect::<Vec<String>>());
 }
 
 #[aoc(day10, part1)]
@@ -114,22 +110,4 @@ fn check_for_corrupted_line(navsys_line: &String) -> (u64, VecDeque<char>) {
     return (0, brace_stack);
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d10_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day10.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(464991, result);
-    }
-
-    #[test]
-    fn test_d10_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day10.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(3662008566, result);
-    }
-}
+day_solution!(Day10, 10, Vec<String>);
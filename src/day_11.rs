@@ -1,22 +1,59 @@
 use std::collections::HashSet;
 
+use super::simulation::Simulation;
 use super::utils::map::*;
+use super::utils::parse;
+use super::utils::render::*;
 
-#[aoc_generator(day11)]
-fn parse_input(input: &str) -> Vec<Vec<u64>> {
-    let mut output: Vec<Vec<u64>> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        output.push(
-            line.chars()
-                .map(|x| x.to_digit(10).unwrap() as u64)
-                .collect::<Vec<u64>>(),
-        );
+/// Octopus energy levels that advance by one step of flashes on each step of the simulation.
+pub struct OctopusSimulation {
+    octopus_map: Vec<Vec<u64>>,
+    total_flash_count: usize,
+}
+
+impl OctopusSimulation {
+    /// Creates a new simulation from the energy levels given in the puzzle input.
+    pub fn from_input(input: &str) -> Result<Self, parse::ParseError> {
+        return Ok(Self {
+            octopus_map: parse_input(input)?,
+            total_flash_count: 0,
+        });
+    }
+
+    /// Gets the current energy level of each octopus, indexed by [y][x].
+    pub fn octopus_map(&self) -> &[Vec<u64>] {
+        return &self.octopus_map;
+    }
+
+    /// Gets the total number of flashes over all steps taken so far.
+    pub fn total_flash_count(&self) -> usize {
+        return self.total_flash_count;
+    }
+}
+
+impl Simulation for OctopusSimulation {
+    fn step(&mut self) {
+        self.total_flash_count += conduct_step_octopus_map(&mut self.octopus_map);
+    }
+
+    /// Draws the energy levels from dark (1) to bright (9), with octopii that flashed on the last
+    /// step (energy level 0) in white.
+    fn snapshot(&self) -> GridImage {
+        let energy = Palette::gradient(Colour::new(0, 0, 64), Colour::new(0, 160, 160), 9);
+        let palette = Palette::new(
+            [Colour::WHITE]
+                .into_iter()
+                .chain((0..9).map(|level| energy.colour(level)))
+                .collect::<Vec<Colour>>(),
+        )
+        .unwrap();
+        return GridImage::from_values(&self.octopus_map, &palette).unwrap();
     }
-    return output;
+}
+
+#[aoc_generator(day11)]
+fn parse_input(input: &str) -> Result<Vec<Vec<u64>>, parse::ParseError> {
+    return parse::digit_grid::<u64>(input);
 }
 
 #[aoc(day11, part1)]
@@ -97,22 +134,28 @@ fn conduct_step_octopus_map(octopus_map: &mut Vec<Vec<u64>>) -> usize {
     return flash_locations.len();
 }
 
+day_solution!(Day11, 11, Vec<Vec<u64>>);
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs::*;
 
-    #[test]
-    fn test_d11_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day11.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(1617, result);
-    }
+    const EXAMPLE_MAP: &str = "5483143223\n2745854711\n5264556173\n6141336146\n6357385478\n\
+                               4167524645\n2176841721\n6882881134\n4846848554\n5283751526\n";
 
     #[test]
-    fn test_d11_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day11.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(258, result);
+    fn test_d11_simulation_snapshot() {
+        let mut simulation = OctopusSimulation::from_input(EXAMPLE_MAP).unwrap();
+        for _ in 0..10 {
+            simulation.step();
+        }
+        assert_eq!(204, simulation.total_flash_count());
+        let image = simulation.snapshot();
+        assert_eq!((10, 10), (image.width(), image.height()));
+        // Top-left octopus has energy level 0 after step 10, and the one beside it has level 4
+        assert_eq!(0, simulation.octopus_map()[0][0]);
+        assert_eq!(Some(Colour::WHITE), image.get(0, 0));
+        assert_eq!(4, simulation.octopus_map()[0][1]);
+        assert_ne!(Some(Colour::WHITE), image.get(1, 0));
     }
 }
@@ -1,25 +1,25 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use super::utils::parse;
+
 #[aoc_generator(day12)]
-fn parse_input(input: &str) -> HashMap<String, HashSet<String>> {
+fn parse_input(input: &str) -> Result<HashMap<String, HashSet<String>>, parse::ParseError> {
     let mut cave_graph: HashMap<String, HashSet<String>> = HashMap::new();
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let pair = line.split("-").collect::<Vec<&str>>();
+    for (line_num, line) in parse::lines(input) {
+        let (cave_1, cave_2) = line.split_once('-').ok_or_else(|| {
+            parse::ParseError::new(line_num, &format!("bad cave connection: {}", line))
+        })?;
         cave_graph
-            .entry(pair[0].to_string())
+            .entry(cave_1.to_string())
             .or_insert(HashSet::new())
-            .insert(pair[1].to_string());
+            .insert(cave_2.to_string());
         cave_graph
-            .entry(pair[1].to_string())
+            .entry(cave_2.to_string())
             .or_insert(HashSet::new())
-            .insert(pair[0].to_string());
+            .insert(cave_1.to_string());
     }
-    return cave_graph;
+    return Ok(cave_graph);
 }
 
 #[aoc(day12, part1)]
@@ -98,22 +98,4 @@ fn visit_nodes(
     return total_paths;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d12_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day12.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(3779, result);
-    }
-
-    #[test]
-    fn test_d12_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day12.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(96988, result);
-    }
-}
+day_solution!(Day12, 12, HashMap<String, HashSet<String>>);
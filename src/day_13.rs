@@ -1,129 +1,270 @@
 use std::collections::HashSet;
 
 use super::utils::map::Point2D;
+use super::utils::parse;
 
-#[aoc_generator(day13)]
-fn parse_input(input: &str) -> (HashSet<Point2D>, Vec<Point2D>) {
-    let mut points_map: HashSet<Point2D> = HashSet::new();
-    let mut fold_instructions: Vec<Point2D> = vec![];
-    let mut points_map_check = true;
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            points_map_check = false;
-            continue;
+/// Axis of the line along which the transparent paper is folded.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Represents a single fold instruction - the paper is folded along the given line on the axis.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Fold {
+    axis: Axis,
+    line: i64,
+}
+
+impl Fold {
+    pub fn new(axis: Axis, line: i64) -> Self {
+        Self {
+            axis: axis,
+            line: line,
+        }
+    }
+
+    /// Checks if the given point lies on the fold line.
+    pub fn is_on_line(&self, point: &Point2D) -> bool {
+        match self.axis {
+            Axis::X => point.get_x() == self.line,
+            Axis::Y => point.get_y() == self.line,
+        }
+    }
+
+    /// Determines where the given point ends up after the fold. Points beyond the fold line are
+    /// reflected back across it, and all other points are unchanged.
+    pub fn transform(&self, point: &Point2D) -> Point2D {
+        match self.axis {
+            Axis::X => Point2D::new(reflect_coord(point.get_x(), self.line), point.get_y()),
+            Axis::Y => Point2D::new(point.get_x(), reflect_coord(point.get_y(), self.line)),
+        }
+    }
+}
+
+/// Composition of a sequence of folds into a single transform applied to each dot. Folds along
+/// different axes commute, so the fold lines are kept separately for each axis.
+struct FoldTransform {
+    x_lines: Vec<i64>,
+    y_lines: Vec<i64>,
+}
+
+impl FoldTransform {
+    /// Composes the given fold instructions, in the order they are to be applied.
+    pub fn new(folds: &[Fold]) -> Self {
+        let x_lines = folds
+            .iter()
+            .filter(|f| f.axis == Axis::X)
+            .map(|f| f.line)
+            .collect::<Vec<i64>>();
+        let y_lines = folds
+            .iter()
+            .filter(|f| f.axis == Axis::Y)
+            .map(|f| f.line)
+            .collect::<Vec<i64>>();
+        Self {
+            x_lines: x_lines,
+            y_lines: y_lines,
+        }
+    }
+
+    /// Applies all composed folds to the given point. Returns the fold that the point would lie on
+    /// if it is on a fold line at the time that fold is applied.
+    pub fn apply(&self, point: &Point2D) -> Result<Point2D, Fold> {
+        let x =
+            apply_fold_lines(point.get_x(), &self.x_lines).map_err(|l| Fold::new(Axis::X, l))?;
+        let y =
+            apply_fold_lines(point.get_y(), &self.y_lines).map_err(|l| Fold::new(Axis::Y, l))?;
+        return Ok(Point2D::new(x, y));
+    }
+}
+
+/// Sheet of transparent paper with dots marked at a sparse set of locations.
+#[derive(Clone, Debug)]
+pub struct Paper {
+    dots: HashSet<Point2D>,
+}
+
+impl Paper {
+    pub fn new(dots: HashSet<Point2D>) -> Self {
+        Self { dots: dots }
+    }
+
+    /// Gets the number of visible dots on the paper.
+    pub fn dot_count(&self) -> usize {
+        return self.dots.len();
+    }
+
+    /// Folds the paper using the given fold instruction. Returns an error if any dot lies on the
+    /// fold line.
+    pub fn fold(&self, fold: &Fold) -> Result<Paper, String> {
+        let mut new_dots: HashSet<Point2D> = HashSet::new();
+        for dot in self.dots.iter() {
+            if fold.is_on_line(dot) {
+                return Err(format!(
+                    "Day 13 - dot {:?} lies on fold line {:?}",
+                    dot, fold
+                ));
+            }
+            new_dots.insert(fold.transform(dot));
+        }
+        return Ok(Paper::new(new_dots));
+    }
+
+    /// Folds the paper using all of the given fold instructions, applying the composed transform
+    /// to each dot once. Returns an error if any dot lies on a fold line when it is folded.
+    pub fn fold_all(&self, folds: &[Fold]) -> Result<Paper, String> {
+        let transform = FoldTransform::new(folds);
+        let mut new_dots: HashSet<Point2D> = HashSet::new();
+        for dot in self.dots.iter() {
+            match transform.apply(dot) {
+                Ok(new_dot) => new_dots.insert(new_dot),
+                Err(fold) => {
+                    return Err(format!(
+                        "Day 13 - dot {:?} lies on fold line {:?}",
+                        dot, fold
+                    ))
+                }
+            };
+        }
+        return Ok(Paper::new(new_dots));
+    }
+
+    /// Converts the paper into a dense grid, indexed by [y][x]. The grid covers the origin and all
+    /// dots, with the top-left cell at the smallest x- and y-values.
+    pub fn to_grid(&self) -> Vec<Vec<bool>> {
+        if self.dots.is_empty() {
+            return vec![];
         }
-        if points_map_check {
-            let coords = line
-                .split(",")
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-            points_map.insert(Point2D::new(coords[0], coords[1]));
-        } else {
-            if line.contains("x") {
-                let split = line.split("=").collect::<Vec<&str>>();
-                let coord = split[1].parse::<i64>().unwrap();
-                fold_instructions.push(Point2D::new(coord, -1));
-            } else {
-                // line.contains("y")
-                let split = line.split("=").collect::<Vec<&str>>();
-                let coord = split[1].parse::<i64>().unwrap();
-                fold_instructions.push(Point2D::new(-1, coord));
+        let x_min = self.dots.iter().map(|p| p.get_x()).min().unwrap().min(0);
+        let x_max = self.dots.iter().map(|p| p.get_x()).max().unwrap();
+        let y_min = self.dots.iter().map(|p| p.get_y()).min().unwrap().min(0);
+        let y_max = self.dots.iter().map(|p| p.get_y()).max().unwrap();
+        let mut grid: Vec<Vec<bool>> = vec![];
+        for y in y_min..=y_max {
+            let row = (x_min..=x_max)
+                .map(|x| self.dots.contains(&Point2D::new(x, y)))
+                .collect::<Vec<bool>>();
+            grid.push(row);
+        }
+        return grid;
+    }
+
+    /// Renders the paper as a string, with '#' for dots and ' ' for empty locations. Each row
+    /// (including the first) is preceded by a newline.
+    pub fn render(&self) -> String {
+        let mut output = String::from('\n');
+        for row in self.to_grid() {
+            for dot in row {
+                if dot {
+                    output.push('#');
+                } else {
+                    output.push(' ');
+                }
             }
+            output.push('\n');
         }
+        return output;
     }
-    return (points_map, fold_instructions);
+}
+
+#[aoc_generator(day13)]
+fn parse_input(input: &str) -> Result<(Paper, Vec<Fold>), parse::ParseError> {
+    let mut points_map: HashSet<Point2D> = HashSet::new();
+    let mut fold_instructions: Vec<Fold> = vec![];
+    let blocks = parse::blocks(input);
+    if blocks.len() != 2 {
+        return Err(parse::ParseError::new(
+            1,
+            "expected dots and fold instructions separated by a blank line",
+        ));
+    }
+    for (line_num, line) in blocks[0].iter() {
+        points_map.insert(parse::value::<Point2D>(*line_num, line)?);
+    }
+    let fold_regex = cached_regex!(r"^fold along (x|y)=(-?\d+)$");
+    for (line_num, line) in blocks[1].iter() {
+        let captures = parse::captures(fold_regex, *line_num, line)?;
+        let axis = match &captures[1] {
+            "x" => Axis::X,
+            _ => Axis::Y,
+        };
+        let coord = parse::value::<i64>(*line_num, &captures[2])?;
+        fold_instructions.push(Fold::new(axis, coord));
+    }
+    return Ok((Paper::new(points_map), fold_instructions));
 }
 
 #[aoc(day13, part1)]
-fn solve_part_1(dot_instructions: &(HashSet<Point2D>, Vec<Point2D>)) -> usize {
+fn solve_part_1(paper_folds: &(Paper, Vec<Fold>)) -> usize {
     // Apply only the first fold instruction
-    let new_dot_locs = apply_fold_instruction(&dot_instructions.0, &dot_instructions.1[0]);
-    return new_dot_locs.len();
+    let paper = paper_folds.0.fold(&paper_folds.1[0]).unwrap();
+    return paper.dot_count();
 }
 
 #[aoc(day13, part2)]
-fn solve_part_2(dot_instructions: &(HashSet<Point2D>, Vec<Point2D>)) -> String {
-    // Apply all fold instructions
-    let mut dot_locations = dot_instructions.0.clone();
-    for fold_instr in dot_instructions.1.iter() {
-        dot_locations = apply_fold_instruction(&dot_locations, &fold_instr);
-    }
-    // Determine string representation of resulting dot locations
-    let x_max = dot_locations.iter().map(|x| x.get_x()).max().unwrap();
-    let y_max = dot_locations.iter().map(|x| x.get_y()).max().unwrap();
-    let mut output = String::from('\n');
-    for y in 0..=y_max {
-        for x in 0..=x_max {
-            let point = Point2D::new(x, y);
-            if dot_locations.contains(&point) {
-                output.push('#');
-            } else {
-                output.push(' ');
-            }
-        }
-        output.push('\n');
+fn solve_part_2(paper_folds: &(Paper, Vec<Fold>)) -> String {
+    // Apply all fold instructions, then determine string representation of resulting dots
+    let paper = paper_folds.0.fold_all(&paper_folds.1).unwrap();
+    return paper.render();
+}
+
+/// Reflects the coordinate back across the fold line if it lies beyond the line.
+fn reflect_coord(coord: i64, line: i64) -> i64 {
+    if coord > line {
+        return 2 * line - coord;
     }
-    return output;
+    return coord;
 }
 
-/// Determines the resulting transformation of the dot locations by applying the given fold
-/// instruction. x-fold instruction is noted by a y-co-ordinate of -1, and vice verse for a y-fold
-/// instruction.
-fn apply_fold_instruction(dot_locs: &HashSet<Point2D>, fold_instr: &Point2D) -> HashSet<Point2D> {
-    let mut new_dot_locs: HashSet<Point2D> = HashSet::new();
-    for dot_loc in dot_locs.iter() {
-        // x-fold instruction
-        if fold_instr.get_x() > 0 {
-            // Dot is not on the half being folded up
-            if dot_loc.get_x() <= fold_instr.get_x() {
-                new_dot_locs.insert(*dot_loc);
-            } else {
-                // Calculate new x
-                let delta_x = dot_loc.get_x() - fold_instr.get_x();
-                let new_dot = Point2D::new(fold_instr.get_x() - delta_x, dot_loc.get_y());
-                new_dot_locs.insert(new_dot);
-            }
-        // y-fold instruction
-        } else {
-            // Dot is not on the half being folded up
-            if dot_loc.get_y() <= fold_instr.get_y() {
-                new_dot_locs.insert(*dot_loc);
-            } else {
-                // Calculate new y
-                let delta_y = dot_loc.get_y() - fold_instr.get_y();
-                let new_dot = Point2D::new(dot_loc.get_x(), fold_instr.get_y() - delta_y);
-                new_dot_locs.insert(new_dot);
-            }
+/// Applies each of the fold lines to the coordinate in turn. Returns the offending line as an error
+/// if the coordinate lies on a fold line when that fold is applied.
+fn apply_fold_lines(coord: i64, lines: &[i64]) -> Result<i64, i64> {
+    let mut coord = coord;
+    for line in lines {
+        if coord == *line {
+            return Err(*line);
         }
+        coord = reflect_coord(coord, *line);
     }
-    return new_dot_locs;
+    return Ok(coord);
 }
 
+day_solution!(Day13, 13, (Paper, Vec<Fold>));
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::fs::*;
 
     #[test]
-    fn test_d13_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day13.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(607, result);
+    fn test_d13_fold_all_matches_sequential() {
+        let (paper, folds) =
+            parse_input(&read_to_string("./input/2021/test/day_13_test_001.txt").unwrap()).unwrap();
+        let mut sequential = paper.clone();
+        for fold in folds.iter() {
+            sequential = sequential.fold(fold).unwrap();
+        }
+        let composed = paper.fold_all(&folds).unwrap();
+        assert_eq!(sequential.dots, composed.dots);
+        assert_eq!(16, composed.dot_count());
+    }
+
+    #[test]
+    fn test_d13_fold_along_x_zero() {
+        let paper = Paper::new(HashSet::from([Point2D::new(2, 1), Point2D::new(3, 0)]));
+        let result = paper.fold(&Fold::new(Axis::X, 0)).unwrap();
+        let expected = HashSet::from([Point2D::new(-2, 1), Point2D::new(-3, 0)]);
+        assert_eq!(expected, result.dots);
     }
 
     #[test]
-    fn test_d13_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day13.txt").unwrap());
-        let result = solve_part_2(&input);
-        // Expected represents the capital letters "CPZLPFZL"
-        let expected = String::from(
-            "\n ##  ###  #### #    ###  #### #### #   \n\
-            #  # #  #    # #    #  # #       # #   \n\
-            #    #  #   #  #    #  # ###    #  #   \n\
-            #    ###   #   #    ###  #     #   #   \n\
-            #  # #    #    #    #    #    #    #   \n ##  #    #### #### #    #    #### ####\n",
-        );
-        assert_eq!(expected, result);
+    fn test_d13_dot_on_fold_line() {
+        let paper = Paper::new(HashSet::from([Point2D::new(1, 7), Point2D::new(3, 2)]));
+        assert!(paper.fold(&Fold::new(Axis::Y, 7)).is_err());
+        assert!(paper
+            .fold_all(&[Fold::new(Axis::X, 5), Fold::new(Axis::Y, 7)])
+            .is_err());
     }
 }
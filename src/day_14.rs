@@ -1,132 +1,276 @@
 use std::collections::HashMap;
 
-#[aoc_generator(day14)]
-fn parse_input(input: &str) -> (String, HashMap<String, char>) {
-    let mut insertion_rules: HashMap<String, char> = HashMap::new();
-    let mut polymer_template = String::new();
-    let mut check_polymer_template = true;
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            check_polymer_template = false;
-            continue;
+use super::utils::parse;
+
+/// Compact representation of the polymer template and pair insertion rules. Elements are mapped to
+/// ids, and element pairs (a, b) are indexed as `a * n + b` where n is the number of elements.
+pub struct Polymer {
+    elements: Vec<char>,
+    template: Vec<usize>,
+    rules: Vec<Option<usize>>,
+}
+
+impl Polymer {
+    /// Creates a new Polymer from the template and pair insertion rules. Returns None if the
+    /// template is empty.
+    pub fn new(template: &str, insertion_rules: &HashMap<(char, char), char>) -> Option<Self> {
+        if template.is_empty() {
+            return None;
+        }
+        // Assign ids to all elements appearing in the template and the rules
+        let mut elements: Vec<char> = vec![];
+        let rule_chars = insertion_rules
+            .iter()
+            .flat_map(|((left, right), insert)| vec![*left, *right, *insert]);
+        for c in template.chars().chain(rule_chars) {
+            if !elements.contains(&c) {
+                elements.push(c);
+            }
+        }
+        elements.sort();
+        let element_id = |c: char| elements.iter().position(|e| *e == c).unwrap();
+        let n = elements.len();
+        let template = template.chars().map(element_id).collect::<Vec<usize>>();
+        let mut rules: Vec<Option<usize>> = vec![None; n * n];
+        for ((left, right), insert) in insertion_rules.iter() {
+            rules[element_id(*left) * n + element_id(*right)] = Some(element_id(*insert));
+        }
+        Some(Self {
+            elements: elements,
+            template: template,
+            rules: rules,
+        })
+    }
+
+    /// Gets the number of distinct element pairs.
+    fn pair_total(&self) -> usize {
+        return self.elements.len() * self.elements.len();
+    }
+
+    /// Determines the count of each element pair in the polymer template.
+    pub fn template_pair_counts(&self) -> Vec<u128> {
+        let n = self.elements.len();
+        let mut pair_counts = vec![0; self.pair_total()];
+        for i in 1..self.template.len() {
+            pair_counts[self.template[i - 1] * n + self.template[i]] += 1;
+        }
+        return pair_counts;
+    }
+
+    /// Applies one step of the pair insertion rules to the given pair counts. Returns None if any
+    /// count would overflow.
+    pub fn apply_step(&self, pair_counts: &[u128]) -> Option<Vec<u128>> {
+        let n = self.elements.len();
+        let mut new_pair_counts: Vec<u128> = vec![0; self.pair_total()];
+        for (pair, count) in pair_counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            for new_pair in self.produced_pairs(pair, n) {
+                new_pair_counts[new_pair] = new_pair_counts[new_pair].checked_add(*count)?;
+            }
+        }
+        return Some(new_pair_counts);
+    }
+
+    /// Determines the pair counts after the given number of steps by repeatedly applying the
+    /// insertion rules, in O(steps * pairs) time. Returns None if any count would overflow.
+    pub fn pair_counts_iterative(&self, steps: u64) -> Option<Vec<u128>> {
+        let mut pair_counts = self.template_pair_counts();
+        for _ in 0..steps {
+            pair_counts = self.apply_step(&pair_counts)?;
+        }
+        return Some(pair_counts);
+    }
+
+    /// Determines the pair counts after the given number of steps by raising the pair transition
+    /// matrix to the power of the step count. Returns None if any count would overflow.
+    pub fn pair_counts_matrix(&self, steps: u64) -> Option<Vec<u128>> {
+        let n = self.elements.len();
+        let size = self.pair_total();
+        // Transition matrix - entry [to][from] is the number of "to" pairs produced by a "from" pair
+        let mut transition: Vec<Vec<u128>> = vec![vec![0; size]; size];
+        for pair in 0..size {
+            for new_pair in self.produced_pairs(pair, n) {
+                transition[new_pair][pair] += 1;
+            }
         }
-        if check_polymer_template {
-            polymer_template = line.to_string();
-        } else {
-            let split = line.split(" -> ").collect::<Vec<&str>>();
-            insertion_rules.insert(split[0].to_string(), split[1].chars().next().unwrap());
+        let power = matrix_power(&transition, steps)?;
+        let template_pair_counts = self.template_pair_counts();
+        let mut pair_counts: Vec<u128> = vec![0; size];
+        for (to, row) in power.iter().enumerate() {
+            for (from, count) in template_pair_counts.iter().enumerate() {
+                pair_counts[to] = pair_counts[to].checked_add(row[from].checked_mul(*count)?)?;
+            }
+        }
+        return Some(pair_counts);
+    }
+
+    /// Determines the count of each element in the polymer after the given number of steps. The
+    /// iterative or matrix exponentiation method is used, depending on which is cheaper for the
+    /// step count. Returns None if any count would overflow.
+    pub fn solve(&self, steps: u64) -> Option<HashMap<char, u128>> {
+        let size = self.pair_total() as u64;
+        let iterative_cost = steps.saturating_mul(size);
+        // Costs saturate rather than overflow for large element alphabets, favouring iteration
+        let matrix_cost = size
+            .saturating_pow(3)
+            .saturating_mul(64 - steps.leading_zeros() as u64);
+        let pair_counts = {
+            if iterative_cost <= matrix_cost {
+                self.pair_counts_iterative(steps)?
+            } else {
+                self.pair_counts_matrix(steps)?
+            }
+        };
+        return self.element_histogram(&pair_counts);
+    }
+
+    /// Calculates the count of each element from the given pair counts. Each element is counted as
+    /// the left element of a pair, plus the last element of the template (which never changes).
+    pub fn element_histogram(&self, pair_counts: &[u128]) -> Option<HashMap<char, u128>> {
+        let n = self.elements.len();
+        let mut counts = vec![0u128; n];
+        for (pair, count) in pair_counts.iter().enumerate() {
+            counts[pair / n] = counts[pair / n].checked_add(*count)?;
+        }
+        if let Some(last) = self.template.last() {
+            counts[*last] = counts[*last].checked_add(1)?;
+        }
+        let histogram = self
+            .elements
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(c, count)| (*c, count))
+            .collect::<HashMap<char, u128>>();
+        return Some(histogram);
+    }
+
+    /// Gets the pairs produced by one step of the insertion rules applied to the given pair.
+    fn produced_pairs(&self, pair: usize, n: usize) -> Vec<usize> {
+        match self.rules[pair] {
+            Some(insert) => vec![(pair / n) * n + insert, insert * n + pair % n],
+            None => vec![pair],
         }
     }
-    return (polymer_template, insertion_rules);
+}
+
+#[aoc_generator(day14)]
+fn parse_input(input: &str) -> Result<Polymer, parse::ParseError> {
+    let mut insertion_rules: HashMap<(char, char), char> = HashMap::new();
+    let blocks = parse::blocks(input);
+    if blocks.len() != 2 || blocks[0].len() != 1 {
+        return Err(parse::ParseError::new(
+            1,
+            "expected polymer template and insertion rules separated by a blank line",
+        ));
+    }
+    let (template_line, polymer_template) = blocks[0][0];
+    let rule_regex = cached_regex!(r"^(\w)(\w) -> (\w)$");
+    for (line_num, line) in blocks[1].iter() {
+        let captures = parse::captures(rule_regex, *line_num, line)?;
+        let element = |i: usize| captures[i].chars().next().unwrap();
+        insertion_rules.insert((element(1), element(2)), element(3));
+    }
+    return Polymer::new(polymer_template, &insertion_rules)
+        .ok_or_else(|| parse::ParseError::new(template_line, "polymer template is empty"));
 }
 
 #[aoc(day14, part1)]
-fn solve_part_1(polymer_data: &(String, HashMap<String, char>)) -> u64 {
-    // Count number of times each distinct element pair appears
-    let mut polymer_pairs = determine_polymer_pair_count(&polymer_data.0);
-    // Apply 10 iterations of insertion pair processing
-    for _ in 0..10 {
-        polymer_pairs = apply_polymer_insertion_rules(&polymer_pairs, &polymer_data.1);
-    }
-    return calculate_character_max_min_difference(&polymer_pairs, &polymer_data.0);
+fn solve_part_1(polymer: &Polymer) -> u128 {
+    let histogram = polymer.solve(10).unwrap();
+    return calculate_max_min_difference(&histogram);
 }
 
 #[aoc(day14, part2)]
-fn solve_part_2(polymer_data: &(String, HashMap<String, char>)) -> u64 {
-    // Count number of times each distinct element pair appears
-    let mut polymer_pairs = determine_polymer_pair_count(&polymer_data.0);
-    // Apply 40 iterations of insertion pair processing
-    for _ in 0..40 {
-        polymer_pairs = apply_polymer_insertion_rules(&polymer_pairs, &polymer_data.1);
-    }
-    return calculate_character_max_min_difference(&polymer_pairs, &polymer_data.0);
+fn solve_part_2(polymer: &Polymer) -> u128 {
+    let histogram = polymer.solve(40).unwrap();
+    return calculate_max_min_difference(&histogram);
 }
 
-/// Applies one iteration of the insertion rules to the given polymer pairs. Return value is the
-/// resulting polymer pairs after applying insertion rules.
-fn apply_polymer_insertion_rules(
-    polymer_pairs: &HashMap<String, u64>,
-    insertion_rules: &HashMap<String, char>,
-) -> HashMap<String, u64> {
-    let mut new_polymer_pairs: HashMap<String, u64> = HashMap::new();
-    for (pair, count) in polymer_pairs.iter() {
-        if insertion_rules.contains_key(pair) {
-            let insertion_char = *insertion_rules.get(pair).unwrap();
-            let old_pair_chars = pair.chars().collect::<Vec<char>>();
-            let mut new_left_pair = String::new();
-            let mut new_right_pair = String::new();
-            new_left_pair.push(old_pair_chars[0]);
-            new_left_pair.push(insertion_char);
-            new_right_pair.push(insertion_char);
-            new_right_pair.push(old_pair_chars[1]);
-            *new_polymer_pairs.entry(new_left_pair).or_insert(0) += count;
-            *new_polymer_pairs.entry(new_right_pair).or_insert(0) += count;
-        } else {
-            *new_polymer_pairs.entry(pair.clone()).or_insert(0) += count;
-        }
-    }
-    return new_polymer_pairs;
+/// Calculates the difference in the counts for the most and least common elements.
+fn calculate_max_min_difference(histogram: &HashMap<char, u128>) -> u128 {
+    let max = histogram.values().max().unwrap();
+    let min = histogram.values().min().unwrap();
+    return max - min;
 }
 
-/// Determines the count of each distinct element pair in the given polymer template.
-fn determine_polymer_pair_count(polymer_template: &String) -> HashMap<String, u64> {
-    let polymer_chars = polymer_template.chars().collect::<Vec<char>>();
-    let mut polymer_pairs: HashMap<String, u64> = HashMap::new();
-    for i in 1..polymer_chars.len() {
-        let current_pair = {
-            let mut s = String::new();
-            s.push(polymer_chars[i - 1]);
-            s.push(polymer_chars[i]);
-            s
-        };
-        *polymer_pairs.entry(current_pair).or_insert(0) += 1;
+/// Raises the given square matrix to the given power by repeated squaring. Returns None if any
+/// entry would overflow.
+fn matrix_power(matrix: &[Vec<u128>], power: u64) -> Option<Vec<Vec<u128>>> {
+    let size = matrix.len();
+    let mut result = (0..size)
+        .map(|i| {
+            (0..size)
+                .map(|j| if i == j { 1 } else { 0 })
+                .collect::<Vec<u128>>()
+        })
+        .collect::<Vec<Vec<u128>>>();
+    let mut base = matrix.to_vec();
+    let mut power = power;
+    while power > 0 {
+        if power & 1 == 1 {
+            result = matrix_multiply(&result, &base)?;
+        }
+        power >>= 1;
+        if power > 0 {
+            base = matrix_multiply(&base, &base)?;
+        }
     }
-    return polymer_pairs;
+    return Some(result);
 }
 
-/// Calculates the difference in the counts for the most and least common characters in the given
-/// polymer template.
-fn calculate_character_max_min_difference(
-    polymer_pairs: &HashMap<String, u64>,
-    original_polymer_template: &String,
-) -> u64 {
-    // Determine character count
-    let polymer_chars = original_polymer_template.chars().collect::<Vec<char>>();
-    let mut char_counts: HashMap<char, u64> = HashMap::new();
-    for (pair, count) in polymer_pairs.iter() {
-        let pair_chars = pair.chars().collect::<Vec<char>>();
-        *char_counts.entry(pair_chars[0]).or_insert(0) += count;
-        *char_counts.entry(pair_chars[1]).or_insert(0) += count;
-    }
-    // Adjust figures for start and end char of original polymer template
-    for (c, count) in char_counts.iter_mut() {
-        *count /= 2;
-        if *c == polymer_chars[0] || *c == polymer_chars[polymer_chars.len() - 1] {
-            *count += 1;
+/// Multiplies the two given square matrices. Returns None if any entry would overflow.
+fn matrix_multiply(left: &[Vec<u128>], right: &[Vec<u128>]) -> Option<Vec<Vec<u128>>> {
+    let size = left.len();
+    let mut output: Vec<Vec<u128>> = vec![vec![0; size]; size];
+    for i in 0..size {
+        for k in 0..size {
+            if left[i][k] == 0 {
+                continue;
+            }
+            for j in 0..size {
+                output[i][j] = output[i][j].checked_add(left[i][k].checked_mul(right[k][j])?)?;
+            }
         }
     }
-    let max = char_counts.values().max().unwrap();
-    let min = char_counts.values().min().unwrap();
-    return max - min;
+    return Some(output);
 }
 
+day_solution!(Day14, 14, Polymer);
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::fs::*;
 
     #[test]
-    fn test_d14_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day14.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(2768, result);
+    fn test_d14_histogram_test_001() {
+        let input =
+            parse_input(&read_to_string("./input/2021/test/day_14_test_001.txt").unwrap()).unwrap();
+        let histogram = input.solve(10).unwrap();
+        assert_eq!(1749, histogram[&'B']);
+        assert_eq!(298, histogram[&'C']);
+        assert_eq!(161, histogram[&'H']);
+        assert_eq!(865, histogram[&'N']);
     }
 
     #[test]
-    fn test_d14_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day14.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(2914365137499, result);
+    fn test_d14_empty_template() {
+        let insertion_rules = HashMap::from([(('A', 'B'), 'C')]);
+        assert!(Polymer::new("", &insertion_rules).is_none());
+        assert!(Polymer::new("AB", &insertion_rules).is_some());
+    }
+
+    #[test]
+    fn test_d14_matrix_matches_iterative() {
+        let input = parse_input(&read_to_string("./input/2021/day14.txt").unwrap()).unwrap();
+        for steps in [0, 1, 10, 40, 100] {
+            assert_eq!(
+                input.pair_counts_iterative(steps),
+                input.pair_counts_matrix(steps)
+            );
+        }
     }
 }
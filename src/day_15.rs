@@ -1,159 +1,182 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 
 use priority_queue::DoublePriorityQueue;
 
 use super::utils::map::*;
+use super::utils::parse;
+use super::utils::render::*;
 
-#[aoc_generator(day15)]
-fn parse_input(input: &str) -> Vec<Vec<u64>> {
-    let mut output: Vec<Vec<u64>> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+/// Lazily evaluated view of a risk map repeated as tiles. The risk in each tile is the base risk
+/// increased by the tile's Manhattan distance from the top-left tile, with values above the wrap
+/// modulus wrapping back around to 1.
+struct TiledRiskMap<'a> {
+    base: &'a Vec<Vec<u64>>,
+    x_tiles: usize,
+    y_tiles: usize,
+    wrap_modulus: u64,
+}
+
+impl<'a> TiledRiskMap<'a> {
+    /// Creates a new tiled view of the base risk map. Returns an error if the base map is empty or
+    /// its rows are of different lengths, if any risk is outside of the range from 1 to the wrap
+    /// modulus, if there are no tiles, or if the wrap modulus is zero.
+    pub fn new(
+        base: &'a Vec<Vec<u64>>,
+        x_tiles: usize,
+        y_tiles: usize,
+        wrap_modulus: u64,
+    ) -> Result<Self, String> {
+        if base.is_empty() || base[0].is_empty() || base.iter().any(|r| r.len() != base[0].len()) {
+            return Err(String::from("risk map is empty or not rectangular"));
         }
-        output.push(
-            line.chars()
-                .map(|x| x.to_digit(10).unwrap() as u64)
-                .collect::<Vec<u64>>(),
-        );
+        if x_tiles == 0 || y_tiles == 0 || wrap_modulus == 0 {
+            return Err(String::from(
+                "tile counts and wrap modulus must be positive",
+            ));
+        }
+        for (y, row) in base.iter().enumerate() {
+            if let Some(x) = row.iter().position(|r| *r == 0 || *r > wrap_modulus) {
+                return Err(format!(
+                    "risk {} at ({}, {}) is outside of the range 1 to {}",
+                    row[x], x, y, wrap_modulus
+                ));
+            }
+        }
+        Ok(Self {
+            base: base,
+            x_tiles: x_tiles,
+            y_tiles: y_tiles,
+            wrap_modulus: wrap_modulus,
+        })
+    }
+
+    /// Gets the width of the full tiled map.
+    pub fn width(&self) -> usize {
+        return self.base[0].len() * self.x_tiles;
     }
-    return output;
+
+    /// Gets the height of the full tiled map.
+    pub fn height(&self) -> usize {
+        return self.base.len() * self.y_tiles;
+    }
+
+    /// Calculates the risk at the given location in the tiled map. Returns None if the location is
+    /// outside of the map.
+    pub fn get_risk(&self, x: usize, y: usize) -> Option<u64> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let base_width = self.base[0].len();
+        let base_height = self.base.len();
+        let tile_offset = (x / base_width + y / base_height) as u64;
+        let base_risk = self.base[y % base_height][x % base_width];
+        return Some((base_risk - 1 + tile_offset) % self.wrap_modulus + 1);
+    }
+}
+
+#[aoc_generator(day15)]
+fn parse_input(input: &str) -> Result<Vec<Vec<u64>>, parse::ParseError> {
+    return parse::digit_grid::<u64>(input);
 }
 
 #[aoc(day15, part1)]
 fn solve_part_1(risk_map: &Vec<Vec<u64>>) -> u64 {
-    let start_node = (0, 0);
-    let end_node = (risk_map.len() - 1, risk_map[0].len() - 1);
-    // Initialise unvisited notes and tentative distances
-    let mut unvisited: HashSet<(usize, usize)> = HashSet::new();
-    let mut dists: Vec<Vec<u64>> = vec![];
-    for y in 0..risk_map.len() {
-        for x in 0..risk_map[y].len() {
-            unvisited.insert((x, y));
-        }
-        dists.push(vec![u64::MAX; risk_map[y].len()]);
-    }
-    dists[0][0] = 0;
-    // Find the path
-    find_shortest_path_dijkstra(start_node, end_node, risk_map, &mut unvisited, &mut dists);
-    // Check the resulting risk score for the bottom right tile
-    return dists[end_node.1][end_node.0];
+    let tiled_map =
+        TiledRiskMap::new(risk_map, 1, 1, 9).unwrap_or_else(|e| panic!("Day 15 - {}", e));
+    return find_lowest_total_risk(&tiled_map);
 }
 
 #[aoc(day15, part2)]
 fn solve_part_2(risk_map: &Vec<Vec<u64>>) -> u64 {
-    // Create new risk map
-    let new_risk_map = transform_risk_map(risk_map);
-    // Initialise unvisited notes and tentative distances
-    let mut unvisited: HashSet<(usize, usize)> = HashSet::new();
-    let mut dists: Vec<Vec<u64>> = vec![];
-    for y in 0..new_risk_map.len() {
-        for x in 0..new_risk_map[y].len() {
-            unvisited.insert((x, y));
-        }
-        dists.push(vec![u64::MAX; new_risk_map[y].len()]);
-    }
-    dists[0][0] = 0;
-    // Shortest path
+    let tiled_map =
+        TiledRiskMap::new(risk_map, 5, 5, 9).unwrap_or_else(|e| panic!("Day 15 - {}", e));
+    return find_lowest_total_risk(&tiled_map);
+}
+
+/// Creates an overlay highlighting the lowest risk path from the top-left to the bottom-right of
+/// the risk map, repeated the given number of tiles across and down, for drawing over an image of
+/// the tiled risk levels. Returns an error if the risk map is not valid.
+pub fn lowest_risk_path_overlay(
+    risk_map: &Vec<Vec<u64>>,
+    tiles: usize,
+    colour: Colour,
+) -> Result<Overlay, String> {
+    let tiled_map = TiledRiskMap::new(risk_map, tiles, tiles, 9)?;
+    let start_node = (0, 0);
+    let end_node = (tiled_map.width() - 1, tiled_map.height() - 1);
+    let (_, path) = find_shortest_path_dijkstra(start_node, end_node, &tiled_map)
+        .ok_or_else(|| String::from("no path through risk map"))?;
+    return Ok(Overlay::new(path, colour));
+}
+
+/// Finds the lowest total risk of any path from the top-left to the bottom-right of the tiled map.
+fn find_lowest_total_risk(tiled_map: &TiledRiskMap) -> u64 {
     let start_node = (0, 0);
-    let end_node = (dists.len() - 1, dists[0].len() - 1);
-    find_shortest_path_dijkstra(
-        start_node,
-        end_node,
-        &new_risk_map,
-        &mut unvisited,
-        &mut dists,
-    );
-    return dists[end_node.1][end_node.0];
+    let end_node = (tiled_map.width() - 1, tiled_map.height() - 1);
+    return find_shortest_path_dijkstra(start_node, end_node, tiled_map)
+        .unwrap()
+        .0;
 }
 
-/// Finds the shortest path from the start node to all other nodes reachable from it.
+/// Finds the shortest path from the start node to the end node, with risk values calculated from
+/// the tiled map as nodes are reached. Returns the total risk of the path and the nodes along it
+/// (including the start and end nodes), or None if the end node is not reachable.
 fn find_shortest_path_dijkstra(
     start_node: (usize, usize),
     end_node: (usize, usize),
-    risk_map: &Vec<Vec<u64>>,
-    unvisited: &mut HashSet<(usize, usize)>,
-    dists: &mut Vec<Vec<u64>>,
-) {
-    // Insert the start node into the visit queye with distance 0
+    tiled_map: &TiledRiskMap,
+) -> Option<(u64, Vec<(usize, usize)>)> {
+    let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    let mut dists: HashMap<(usize, usize), u64> = HashMap::new();
+    // Node from which each node was reached on the shortest path found to it so far
+    let mut previous: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    dists.insert(start_node, 0);
+    // Insert the start node into the visit queue with distance 0
     let mut visit_queue: DoublePriorityQueue<(usize, usize), u64> = DoublePriorityQueue::new();
     visit_queue.push(start_node, 0);
-    while !unvisited.is_empty() {
-        // Stop if there are no more neighbours to visit
-        if visit_queue.is_empty() {
-            return;
-        }
-        // Find the unvisited node with the smallest distance
-        let (current_node, current_dist) = visit_queue.pop_min().unwrap();
+    // Stop if there are no more neighbours to visit
+    while let Some((current_node, current_dist)) = visit_queue.pop_min() {
         if current_node == end_node {
-            return;
+            // Trace the path back to the start node
+            let mut path = vec![end_node];
+            while let Some(node) = previous.get(path.last().unwrap()) {
+                path.push(*node);
+            }
+            path.reverse();
+            return Some((current_dist, path));
         }
         // Mark current node as visited
-        unvisited.remove(&current_node);
-        visit_queue.remove(&current_node);
+        visited.insert(current_node);
         // Determine distance from current node for all unvisited neighbour nodes
         let neighbour_nodes = get_surrounding_points_no_diagonals(
             current_node.0,
             current_node.1,
-            dists[0].len() - 1,
-            dists.len() - 1,
+            tiled_map.width() - 1,
+            tiled_map.height() - 1,
         );
         for neighbour_node in neighbour_nodes {
-            if !unvisited.contains(&neighbour_node) {
+            if visited.contains(&neighbour_node) {
                 continue;
             }
+            let risk = match tiled_map.get_risk(neighbour_node.0, neighbour_node.1) {
+                Some(risk) => risk,
+                None => continue,
+            };
             // Calculate new distance and update if needed
-            let new_distance = current_dist + risk_map[neighbour_node.1][neighbour_node.0];
-            if new_distance < dists[neighbour_node.1][neighbour_node.0] {
-                dists[neighbour_node.1][neighbour_node.0] = new_distance;
+            let new_distance = current_dist + risk;
+            let old_distance = dists.entry(neighbour_node).or_insert(u64::MAX);
+            if new_distance < *old_distance {
+                *old_distance = new_distance;
+                previous.insert(neighbour_node, current_node);
                 visit_queue.push(neighbour_node, new_distance);
             }
         }
     }
+    return None;
 }
 
-/// Transforms the given risk map by expanding into the 5x5 tile construct for Part 2.
-fn transform_risk_map(risk_map: &Vec<Vec<u64>>) -> Vec<Vec<u64>> {
-    let mut new_risk_map: Vec<Vec<u64>> = vec![];
-    // Get the first row of tiles input into new risk map
-    for y in 0..risk_map.len() {
-        let mut new_row: Vec<u64> = vec![];
-        for factor in 0..5 {
-            for x in 0..risk_map[y].len() {
-                let new_value = {
-                    let candidate = risk_map[y][x] + factor;
-                    if candidate > 9 {
-                        candidate - 9
-                    } else {
-                        candidate
-                    }
-                };
-                new_row.push(new_value);
-            }
-        }
-        new_risk_map.push(new_row);
-    }
-    // Now copy each of the rows
-    for factor in 1..5 {
-        for y in 0..risk_map.len() {
-            let mut new_row: Vec<u64> = vec![];
-            for x in 0..new_risk_map[y].len() {
-                let new_value = {
-                    let candidate = new_risk_map[y][x] + factor;
-                    if candidate > 9 {
-                        candidate - 9
-                    } else {
-                        candidate
-                    }
-                };
-                new_row.push(new_value);
-            }
-            new_risk_map.push(new_row);
-        }
-    }
-    return new_risk_map;
-}
+day_solution!(Day15, 15, Vec<Vec<u64>>);
 
 #[cfg(test)]
 mod test {
@@ -161,16 +184,56 @@ mod test {
     use std::fs::*;
 
     #[test]
-    fn test_d15_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day15.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(811, result);
+    fn test_d15_tiled_risk_wraps() {
+        let base = vec![vec![8]];
+        let tiled_map = TiledRiskMap::new(&base, 5, 5, 9).unwrap();
+        let first_row = (0..5)
+            .map(|x| tiled_map.get_risk(x, 0).unwrap())
+            .collect::<Vec<u64>>();
+        assert_eq!(vec![8, 9, 1, 2, 3], first_row);
+        assert_eq!(Some(7), tiled_map.get_risk(4, 4));
+        assert_eq!(None, tiled_map.get_risk(5, 0));
+    }
+
+    #[test]
+    fn test_d15_empty_risk_map() {
+        let base: Vec<Vec<u64>> = vec![];
+        assert!(TiledRiskMap::new(&base, 5, 5, 9).is_err());
+    }
+
+    #[test]
+    fn test_d15_risk_out_of_range() {
+        let base = parse_input("10\n01\n").unwrap();
+        assert_eq!(
+            Err(String::from(
+                "risk 0 at (1, 0) is outside of the range 1 to 9"
+            )),
+            TiledRiskMap::new(&base, 5, 5, 9).map(|_| ())
+        );
+        let base = vec![vec![1, 2], vec![10, 1]];
+        assert_eq!(
+            Err(String::from(
+                "risk 10 at (0, 1) is outside of the range 1 to 9"
+            )),
+            TiledRiskMap::new(&base, 1, 1, 9).map(|_| ())
+        );
     }
 
     #[test]
-    fn test_d15_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day15.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(3012, result);
+    fn test_d15_render_lowest_risk_path() {
+        let input = read_to_string("./input/2021/test/day_15_test_001.txt").unwrap();
+        let risk_map = parse_input(&input).unwrap();
+        let mut image = GridImage::from_values(&risk_map, &Palette::greyscale(10)).unwrap();
+        image.apply_overlay(&lowest_risk_path_overlay(&risk_map, 1, Colour::RED).unwrap());
+        let path = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| image.get(*x, *y) == Some(Colour::RED))
+            .collect::<Vec<(usize, usize)>>();
+        assert!(path.contains(&(0, 0)));
+        assert!(path.contains(&(9, 9)));
+        // Risk of the start position is not counted
+        let total_risk = path.iter().map(|(x, y)| risk_map[*y][*x]).sum::<u64>() - risk_map[0][0];
+        assert_eq!(40, total_risk);
+        assert!(lowest_risk_path_overlay(&vec![], 1, Colour::RED).is_err());
     }
 }
@@ -1,292 +1,832 @@
-#[aoc_generator(day16)]
-fn parse_input(input: &str) -> String {
-    let mut binary_string = String::new();
-    let data = input.lines().next().unwrap().trim().to_ascii_lowercase();
-    for c in data.chars() {
-        let binary_decode = match c {
-            '0' => "0000",
-            '1' => "0001",
-            '2' => "0010",
-            '3' => "0011",
-            '4' => "0100",
-            '5' => "0101",
-            '6' => "0110",
-            '7' => "0111",
-            '8' => "1000",
-            '9' => "1001",
-            'a' => "1010",
-            'b' => "1011",
-            'c' => "1100",
-            'd' => "1101",
-            'e' => "1110",
-            'f' => "1111",
-            _ => panic!("Day 16 - invalid character in input: {}", c),
-        };
-        binary_string.push_str(binary_decode);
+use std::fmt;
+use std::io::BufRead;
+
+use super::utils::parse;
+
+/// Deepest nesting of packets that will be decoded, so that a malformed transmission cannot exhaust
+/// the stack.
+const MAX_PACKET_DEPTH: usize = 64;
+
+/// BITS transmission, held as its hexadecimal digits rather than expanded into individual bits.
+pub struct Transmission {
+    hex: String,
+    line: usize,
+}
+
+impl Transmission {
+    /// Gets a reader over the bits of the transmission.
+    pub fn bits(&self) -> BitReader<&[u8]> {
+        let mut bits = BitReader::new(self.hex.as_bytes());
+        bits.line = self.line;
+        return bits;
+    }
+
+    /// Decodes the outermost packet of the transmission, checking it strictly against the BITS
+    /// specification.
+    pub fn decode(&self) -> Result<Packet, parse::ParseError> {
+        return Decoder::new(self.bits(), DecodeMode::Strict)
+            .decode()
+            .map(|(packet, _)| packet);
     }
-    return binary_string;
 }
 
-#[aoc(day16, part1)]
-fn solve_part_1(binary_string: &String) -> u64 {
-    let mut version_sum = 0;
-    let binary_digits = binary_string.chars().collect::<Vec<char>>();
-    let mut cursor = 0;
-    // Handle the single top-level packet
-    // Check version for current packet
-    let version_id = process_version_or_type_number(&binary_digits, &mut cursor);
-    version_sum += version_id;
-    // Check packet type
-    let type_id = process_version_or_type_number(&binary_digits, &mut cursor);
-    // Handle literal packet
-    if type_id == 4 {
-        let (_literal, _total_packet_chars) =
-            process_literal_packet(&binary_digits, &mut cursor, true);
-    // Handle operator packet
-    } else {
-        let (_result, sub_version_sum, _total_packet_chars) =
-            process_operator_packet(&binary_digits, &mut cursor, type_id, true);
-        version_sum += sub_version_sum;
-    }
-    return version_sum;
+/// Reads the bits of a hexadecimal BITS transmission, most significant bit first. Hexadecimal
+/// digits are taken from the reader one at a time as bits are needed, so the transmission does
+/// not need to be held in memory.
+pub struct BitReader<R: BufRead> {
+    reader: R,
+    line: usize,
+    started: bool,
+    nibble: u8,
+    nibble_bits: u32,
+    position: usize,
+    skip_whitespace: bool,
+    skipped_whitespace: Vec<usize>,
 }
 
-#[aoc(day16, part2)]
-fn solve_part_2(binary_string: &String) -> u64 {
-    let binary_digits = binary_string.chars().collect::<Vec<char>>();
-    let mut cursor = 0;
-    // Handle the single top level packet
-    let _version_id = process_version_or_type_number(&binary_digits, &mut cursor);
-    let type_id = process_version_or_type_number(&binary_digits, &mut cursor);
-    let result = {
-        if type_id == 4 {
-            let (literal, _total_packet_chars) =
-                process_literal_packet(&binary_digits, &mut cursor, true);
-            literal
-        } else {
-            let (result, _sub_version_sum, _total_packet_chars) =
-                process_operator_packet(&binary_digits, &mut cursor, type_id, true);
-            result
+impl<R: BufRead> BitReader<R> {
+    /// Creates a new bit reader over the first non-empty line read from the reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: reader,
+            line: 1,
+            started: false,
+            nibble: 0,
+            nibble_bits: 0,
+            position: 0,
+            skip_whitespace: false,
+            skipped_whitespace: vec![],
         }
-    };
-    return result;
-}
+    }
 
-fn process_operator_packet(
-    binary_digits: &Vec<char>,
-    cursor: &mut usize,
-    op_type: u64,
-    has_zero_padding: bool,
-) -> (u64, u64, usize) {
-    let mut version_sum = 0;
-    // Take into account the packet version and type already processed
-    let mut total_packet_chars = 6;
-    // Determine operator packet type (0 or 1)
-    let operator_mode = binary_digits[*cursor].to_digit(10).unwrap();
-    *cursor += 1;
-    total_packet_chars += 1;
-    // Record values from nested packets
-    let mut sub_results: Vec<u64> = vec![];
-    // Calculate L value
-    let l_val = {
-        let mut l_string = String::new();
-        let len = {
-            if operator_mode == 0 {
-                15
-            } else {
-                11
-            }
-        };
-        for i in 0..len {
-            l_string.push(binary_digits[*cursor + i]);
+    /// Gets the number of bits read so far.
+    pub fn position(&self) -> usize {
+        return self.position;
+    }
+
+    /// Reads the next `count` bits (at most 64) as an unsigned integer. Returns an error if more
+    /// than 64 bits are requested, or if the transmission ends first or contains a character that
+    /// is not a hexadecimal digit.
+    pub fn read_bits(&mut self, count: u32) -> Result<u64, parse::ParseError> {
+        check_bit_count(count, self.line)?;
+        let mut value: u64 = 0;
+        for _ in 0..count {
+            let bit = self.next_bit()?.ok_or_else(|| {
+                parse::ParseError::new(
+                    self.line,
+                    &format!("transmission ended after {} bits", self.position),
+                )
+            })?;
+            value = (value << 1) | bit;
         }
-        *cursor += len;
-        total_packet_chars += len;
-        usize::from_str_radix(&l_string, 2).unwrap()
-    };
-    // Process remainder of operator packet
-    if operator_mode == 0 {
-        let mut total_subpacket_chars = 0;
-        while total_subpacket_chars < l_val {
-            let version_id = process_version_or_type_number(binary_digits, cursor);
-            version_sum += version_id;
-            let type_id = process_version_or_type_number(binary_digits, cursor);
-            if type_id == 4 {
-                let (literal, total_chars) = process_literal_packet(binary_digits, cursor, false);
-                sub_results.push(literal);
-                total_subpacket_chars += total_chars;
-            } else {
-                let (result, sub_version_sum, total_chars) =
-                    process_operator_packet(binary_digits, cursor, type_id, false);
-                sub_results.push(result);
-                version_sum += sub_version_sum;
-                total_subpacket_chars += total_chars;
+        return Ok(value);
+    }
+
+    /// Reads the next bit. Returns None if the transmission has ended, or an error if it contains
+    /// a character that is not a hexadecimal digit.
+    pub fn next_bit(&mut self) -> Result<Option<u64>, parse::ParseError> {
+        if self.nibble_bits == 0 {
+            match self.next_nibble()? {
+                Some(nibble) => self.nibble = nibble,
+                None => return Ok(None),
             }
+            self.nibble_bits = 4;
         }
-        total_packet_chars += total_subpacket_chars;
-    } else {
-        let mut total_subpacket_chars = 0;
-        for _ in 0..l_val {
-            let version_id = process_version_or_type_number(binary_digits, cursor);
-            version_sum += version_id;
-            let type_id = process_version_or_type_number(binary_digits, cursor);
-            if type_id == 4 {
-                let (literal, total_chars) = process_literal_packet(binary_digits, cursor, false);
-                sub_results.push(literal);
-                total_subpacket_chars += total_chars;
+        self.nibble_bits -= 1;
+        self.position += 1;
+        return Ok(Some(((self.nibble >> self.nibble_bits) & 1) as u64));
+    }
+
+    /// Reads the value of the next hexadecimal digit in the transmission, skipping any blank lines
+    /// before it starts. Returns None if the transmission has ended. Whitespace between the digits
+    /// of the transmission is an error unless whitespace is being skipped, in which case the bit
+    /// offset at which it was found is recorded. Whitespace at the end of the line is ignored.
+    fn next_nibble(&mut self) -> Result<Option<u8>, parse::ParseError> {
+        let mut found_whitespace = false;
+        loop {
+            let c = {
+                let buffer = self.reader.fill_buf().map_err(|e| {
+                    parse::ParseError::new(self.line, &format!("failed to read input: {}", e))
+                })?;
+                buffer.first().copied()
+            };
+            let c = match c {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+            if c == b'\n' && self.started {
+                return Ok(None);
+            }
+            self.reader.consume(1);
+            if c == b'\n' {
+                self.line += 1;
+            } else if c.is_ascii_whitespace() {
+                found_whitespace |= self.started;
             } else {
-                let (result, sub_version_sum, total_chars) =
-                    process_operator_packet(binary_digits, cursor, type_id, false);
-                sub_results.push(result);
-                version_sum += sub_version_sum;
-                total_subpacket_chars += total_chars;
+                if found_whitespace {
+                    if !self.skip_whitespace {
+                        return Err(parse::ParseError::new(
+                            self.line,
+                            &format!("bit {}: whitespace inside the transmission", self.position),
+                        ));
+                    }
+                    self.skipped_whitespace.push(self.position);
+                }
+                self.started = true;
+                return match (c as char).to_digit(16) {
+                    Some(d) => Ok(Some(d as u8)),
+                    None => Err(parse::ParseError::new(
+                        self.line,
+                        &format!("invalid hexadecimal character: {}", c as char),
+                    )),
+                };
             }
         }
-        total_packet_chars += total_subpacket_chars;
-    }
-    // Adjust for zero padding
-    if has_zero_padding {
-        *cursor += total_packet_chars % 8;
-    }
-    // Calculate the result for current operator packet
-    let result = match op_type {
-        0 => sub_results.iter().sum::<u64>(),
-        1 => sub_results.iter().product::<u64>(),
-        2 => *sub_results.iter().min().unwrap(),
-        3 => *sub_results.iter().max().unwrap(),
-        5 => {
-            if sub_results[0] > sub_results[1] {
-                1
-            } else {
-                0
+    }
+}
+
+/// Checks that no more bits are being read at once than fit in a u64.
+fn check_bit_count(count: u32, line: usize) -> Result<(), parse::ParseError> {
+    if count > 64 {
+        return Err(parse::ParseError::new(
+            line,
+            &format!(
+                "cannot read {} bits at once, at most 64 fit in a value",
+                count
+            ),
+        ));
+    }
+    return Ok(());
+}
+
+/// How strictly a transmission is checked against the BITS specification while decoding.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DecodeMode {
+    /// Any departure from the specification is an error.
+    Strict,
+    /// Departures from the specification are recorded as warnings and decoding carries on. Bits
+    /// missing from a truncated transmission are read as 0.
+    Lenient,
+}
+
+/// Departure from the BITS specification found while decoding a transmission, along with the bit
+/// offset at which it was found.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PacketWarning {
+    pub bit: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PacketWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bit {}: {}", self.bit, self.message)
+    }
+}
+
+/// How the sub-packets of an operator packet are delimited, as given by its length type ID.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LengthType {
+    /// Length type 0 - the sub-packets span the given number of bits.
+    TotalBits(usize),
+    /// Length type 1 - the given number of sub-packets follow.
+    PacketCount(usize),
+}
+
+/// Contents of a packet, following its version and type ID.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PacketContents {
+    Literal(u64),
+    Operator {
+        length_type: LengthType,
+        sub_packets: Vec<Packet>,
+    },
+}
+
+/// Format used when rendering a decoded packet as text.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PacketFormat {
+    /// Indented tree with one packet per line, showing its header fields and bit offsets.
+    Tree,
+    /// Expression with the operators between their operands, such as `(1 + (2 * 3))`.
+    Infix,
+    /// S-expression with the operators before their operands, such as `(+ 1 (* 2 3))`.
+    SExpression,
+}
+
+/// Decoded BITS packet, including all of its sub-packets. Bit offsets count from the start of the
+/// transmission, with the end offset being one past the last bit of the packet.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Packet {
+    pub version: u64,
+    pub type_id: u64,
+    pub start: usize,
+    pub end: usize,
+    pub contents: PacketContents,
+}
+
+impl Packet {
+    /// Gets the sub-packets of the packet, which is empty for a literal packet.
+    pub fn sub_packets(&self) -> &[Packet] {
+        return match &self.contents {
+            PacketContents::Literal(_) => &[],
+            PacketContents::Operator { sub_packets, .. } => sub_packets,
+        };
+    }
+
+    /// Calculates the sum of the version numbers of the packet and all of its sub-packets.
+    pub fn version_sum(&self) -> u64 {
+        return self.version
+            + self
+                .sub_packets()
+                .iter()
+                .map(|p| p.version_sum())
+                .sum::<u64>();
+    }
+
+    /// Calculates the value of the packet. Returns an error if a sum or product overflows a u64, if
+    /// a minimum or maximum packet has no sub-packets, or if a comparison packet does not have
+    /// exactly two sub-packets.
+    pub fn evaluate(&self) -> Result<u64, String> {
+        let sub_packets = match &self.contents {
+            PacketContents::Literal(value) => return Ok(*value),
+            PacketContents::Operator { sub_packets, .. } => sub_packets,
+        };
+        let values = sub_packets
+            .iter()
+            .map(|p| p.evaluate())
+            .collect::<Result<Vec<u64>, String>>()?;
+        let overflow = || format!("{} overflows u64", self.describe());
+        let value = match self.type_id {
+            0 => values
+                .iter()
+                .try_fold(0u64, |total, v| total.checked_add(*v))
+                .ok_or_else(overflow)?,
+            1 => values
+                .iter()
+                .try_fold(1u64, |total, v| total.checked_mul(*v))
+                .ok_or_else(overflow)?,
+            2 => values
+                .iter()
+                .copied()
+                .min()
+                .ok_or_else(|| format!("{} has no sub-packets", self.describe()))?,
+            3 => values
+                .iter()
+                .copied()
+                .max()
+                .ok_or_else(|| format!("{} has no sub-packets", self.describe()))?,
+            _ => {
+                if values.len() != 2 {
+                    return Err(format!(
+                        "{} has {} sub-packets, expected 2",
+                        self.describe(),
+                        values.len()
+                    ));
+                }
+                let is_true = match self.type_id {
+                    5 => values[0] > values[1],
+                    6 => values[0] < values[1],
+                    _ => values[0] == values[1],
+                };
+                is_true as u64
+            }
+        };
+        return Ok(value);
+    }
+
+    /// Renders the packet and all of its sub-packets as text in the given format.
+    pub fn render(&self, format: PacketFormat) -> String {
+        return match format {
+            PacketFormat::Tree => {
+                let mut output = String::new();
+                self.render_tree(0, &mut output);
+                output
+            }
+            PacketFormat::Infix => self.render_infix(),
+            PacketFormat::SExpression => self.render_sexpr(),
+        };
+    }
+
+    /// Gets the name of the packet's type.
+    fn type_name(&self) -> &'static str {
+        return match self.type_id {
+            0 => "sum",
+            1 => "product",
+            2 => "minimum",
+            3 => "maximum",
+            4 => "literal",
+            5 => "greater-than",
+            6 => "less-than",
+            _ => "equal-to",
+        };
+    }
+
+    /// Gets the symbol used for the packet's operator in expressions.
+    fn symbol(&self) -> &'static str {
+        return match self.type_id {
+            0 => "+",
+            1 => "*",
+            2 => "min",
+            3 => "max",
+            5 => ">",
+            6 => "<",
+            _ => "=",
+        };
+    }
+
+    /// Describes the packet by its type and bit offsets, for use in error messages.
+    fn describe(&self) -> String {
+        return format!(
+            "{} packet at bits {}..{}",
+            self.type_name(),
+            self.start,
+            self.end
+        );
+    }
+
+    /// Appends a line for the packet to the output, followed by the lines for its sub-packets
+    /// indented one level further.
+    fn render_tree(&self, depth: usize, output: &mut String) {
+        output.push_str(&format!(
+            "{}v{} {} (type {}) bits {}..{}",
+            "  ".repeat(depth),
+            self.version,
+            self.type_name(),
+            self.type_id,
+            self.start,
+            self.end
+        ));
+        match &self.contents {
+            PacketContents::Literal(value) => output.push_str(&format!(" = {}\n", value)),
+            PacketContents::Operator {
+                length_type,
+                sub_packets,
+            } => {
+                match length_type {
+                    LengthType::TotalBits(bits) => {
+                        output.push_str(&format!(", length type 0 ({} bits)\n", bits))
+                    }
+                    LengthType::PacketCount(count) => {
+                        output.push_str(&format!(", length type 1 ({} sub-packets)\n", count))
+                    }
+                }
+                for sub_packet in sub_packets {
+                    sub_packet.render_tree(depth + 1, output);
+                }
             }
         }
-        6 => {
-            if sub_results[0] < sub_results[1] {
-                1
-            } else {
-                0
+    }
+
+    /// Renders the packet as an infix expression. Minimum, maximum and any sum or product with
+    /// fewer than two operands are rendered as function calls.
+    fn render_infix(&self) -> String {
+        let operands = match &self.contents {
+            PacketContents::Literal(value) => return value.to_string(),
+            PacketContents::Operator { sub_packets, .. } => sub_packets
+                .iter()
+                .map(|p| p.render_infix())
+                .collect::<Vec<String>>(),
+        };
+        if self.type_id == 2 || self.type_id == 3 {
+            return format!("{}({})", self.symbol(), operands.join(", "));
+        }
+        if operands.len() < 2 {
+            return format!("{}({})", self.type_name(), operands.join(", "));
+        }
+        return format!("({})", operands.join(&format!(" {} ", self.symbol())));
+    }
+
+    /// Renders the packet as an S-expression.
+    fn render_sexpr(&self) -> String {
+        return match &self.contents {
+            PacketContents::Literal(value) => value.to_string(),
+            PacketContents::Operator { sub_packets, .. } => {
+                let mut terms = vec![self.symbol().to_string()];
+                terms.extend(sub_packets.iter().map(|p| p.render_sexpr()));
+                format!("({})", terms.join(" "))
             }
+        };
+    }
+}
+
+#[aoc_generator(day16)]
+fn parse_input(input: &str) -> Result<Transmission, parse::ParseError> {
+    return read_transmission(input.as_bytes());
+}
+
+/// Reads the transmission from the first non-empty line read from the reader.
+pub fn read_transmission<R: BufRead>(reader: R) -> Result<Transmission, parse::ParseError> {
+    let (line_num, hex) = parse::read_lines(reader)
+        .next()
+        .unwrap_or_else(|| Err(parse::ParseError::new(1, "input is empty")))?;
+    if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(parse::ParseError::new(
+            line_num,
+            &format!("invalid hexadecimal character: {}", c),
+        ));
+    }
+    return Ok(Transmission {
+        hex: hex,
+        line: line_num,
+    });
+}
+
+/// Decodes the outermost packet of the transmission read from the reader, without holding the
+/// transmission itself in memory. The transmission is checked strictly against the BITS
+/// specification.
+pub fn decode_transmission<R: BufRead>(reader: R) -> Result<Packet, parse::ParseError> {
+    return Decoder::new(BitReader::new(reader), DecodeMode::Strict)
+        .decode()
+        .map(|(packet, _)| packet);
+}
+
+/// Decodes the outermost packet of the transmission read from the reader in lenient mode, returning
+/// the warnings raised along with the packet. Returns an error only if the transmission cannot be
+/// read or contains a character that is not a hexadecimal digit.
+pub fn decode_transmission_lenient<R: BufRead>(
+    reader: R,
+) -> Result<(Packet, Vec<PacketWarning>), parse::ParseError> {
+    return Decoder::new(BitReader::new(reader), DecodeMode::Lenient).decode();
+}
+
+#[aoc(day16, part1)]
+fn solve_part_1(transmission: &Transmission) -> u64 {
+    return transmission
+        .decode()
+        .unwrap_or_else(|e| panic!("Day 16 - bad transmission: {}", e))
+        .version_sum();
+}
+
+#[aoc(day16, part2)]
+fn solve_part_2(transmission: &Transmission) -> u64 {
+    return transmission
+        .decode()
+        .unwrap_or_else(|e| panic!("Day 16 - bad transmission: {}", e))
+        .evaluate()
+        .unwrap_or_else(|e| panic!("Day 16 - {}", e));
+}
+
+/// Decodes packets from the bits of a transmission, checking them against the BITS specification
+/// in the given mode.
+struct Decoder<R: BufRead> {
+    bits: BitReader<R>,
+    mode: DecodeMode,
+    warnings: Vec<PacketWarning>,
+    truncated: bool,
+}
+
+impl<R: BufRead> Decoder<R> {
+    /// Creates a new decoder over the bits. Whitespace inside the transmission is skipped in
+    /// lenient mode.
+    fn new(bits: BitReader<R>, mode: DecodeMode) -> Self {
+        let mut bits = bits;
+        bits.skip_whitespace = mode == DecodeMode::Lenient;
+        Self {
+            bits: bits,
+            mode: mode,
+            warnings: vec![],
+            truncated: false,
         }
-        7 => {
-            if sub_results[0] == sub_results[1] {
-                1
-            } else {
-                0
+    }
+
+    /// Decodes the outermost packet and checks that only zero bits follow it. Returns the packet
+    /// along with any warnings raised in lenient mode.
+    fn decode(mut self) -> Result<(Packet, Vec<PacketWarning>), parse::ParseError> {
+        let packet = self.decode_packet(1)?;
+        // Padding after the outermost packet must be all zeros
+        while let Some(bit) = self.bits.next_bit()? {
+            self.report_skipped_whitespace()?;
+            if bit == 1 {
+                self.report(
+                    self.bits.position() - 1,
+                    String::from("non-zero padding after the outermost packet"),
+                )?;
+                break;
             }
         }
-        _ => panic!("Day 16 - bad operator packet type ID: {}", op_type),
-    };
-    return (result, version_sum, total_packet_chars);
-}
+        return Ok((packet, self.warnings));
+    }
 
-/// Processes a literal value
-fn process_literal_packet(
-    binary_digits: &Vec<char>,
-    cursor: &mut usize,
-    has_zero_padding: bool,
-) -> (u64, usize) {
-    let mut literal_string = String::new();
-    let mut final_group_observed = false;
-    // Take into account the packet version and type already processed
-    let mut total_packet_chars = 6;
-    while !final_group_observed {
-        // Check for final group in packet
-        if binary_digits[*cursor] == '0' {
-            final_group_observed = true;
+    /// Reports a departure from the specification, which is an error in strict mode and a warning
+    /// in lenient mode.
+    fn report(&mut self, bit: usize, message: String) -> Result<(), parse::ParseError> {
+        let warning = PacketWarning {
+            bit: bit,
+            message: message,
+        };
+        match self.mode {
+            DecodeMode::Strict => Err(parse::ParseError::new(self.bits.line, &warning.to_string())),
+            DecodeMode::Lenient => {
+                self.warnings.push(warning);
+                Ok(())
+            }
         }
-        for i in 1..=4 {
-            literal_string.push(binary_digits[*cursor + i]);
+    }
+
+    /// Reports each place that whitespace was skipped inside the transmission since the last call.
+    fn report_skipped_whitespace(&mut self) -> Result<(), parse::ParseError> {
+        for bit in std::mem::take(&mut self.bits.skipped_whitespace) {
+            self.report(bit, String::from("whitespace inside the transmission"))?;
         }
-        *cursor += 5;
-        total_packet_chars += 5;
+        return Ok(());
     }
-    // Adjust cursor to account for zero-padding
-    if has_zero_padding {
-        *cursor += total_packet_chars % 8;
+
+    /// Reads the next `count` bits (at most 64) as an unsigned integer. If the transmission ends
+    /// first, this is an error in strict mode, and in lenient mode the missing bits are read as 0.
+    fn read_bits(&mut self, count: u32) -> Result<u64, parse::ParseError> {
+        if self.mode == DecodeMode::Strict {
+            return self.bits.read_bits(count);
+        }
+        check_bit_count(count, self.bits.line)?;
+        let mut value: u64 = 0;
+        for _ in 0..count {
+            let bit = match self.bits.next_bit()? {
+                Some(bit) => bit,
+                None => {
+                    if !self.truncated {
+                        self.truncated = true;
+                        self.report(
+                            self.bits.position(),
+                            String::from("transmission ended early, reading missing bits as 0"),
+                        )?;
+                    }
+                    self.bits.position += 1;
+                    0
+                }
+            };
+            value = (value << 1) | bit;
+        }
+        self.report_skipped_whitespace()?;
+        return Ok(value);
     }
-    return (
-        u64::from_str_radix(&literal_string, 2).unwrap(),
-        total_packet_chars,
-    );
-}
 
-/// Takes the three characters starting from the cursor and converts them into an integer value.
-/// Cursor is advanced three places.
-fn process_version_or_type_number(binary_digits: &Vec<char>, cursor: &mut usize) -> u64 {
-    let mut digits = String::new();
-    for i in 0..3 {
-        digits.push(binary_digits[*cursor + i]);
+    /// Decodes the next packet at the given depth of nesting (1 for the outermost packet),
+    /// including all of its sub-packets. Returns an error if packets are nested too deeply.
+    fn decode_packet(&mut self, depth: usize) -> Result<Packet, parse::ParseError> {
+        let start = self.bits.position();
+        if depth > MAX_PACKET_DEPTH {
+            return Err(parse::ParseError::new(
+                self.bits.line,
+                &format!(
+                    "bit {}: packets nested more than {} deep",
+                    start, MAX_PACKET_DEPTH
+                ),
+            ));
+        }
+        let version = self.read_bits(3)?;
+        let type_id = self.read_bits(3)?;
+        // Handle literal packet - groups of four bits, each prefixed by a bit set on all but the last
+        if type_id == 4 {
+            let mut value: u64 = 0;
+            let mut overflowed = false;
+            loop {
+                let group = self.read_bits(5)?;
+                overflowed |= value >> 60 != 0;
+                value = (value << 4) | (group & 0xf);
+                if group & 0x10 == 0 {
+                    break;
+                }
+            }
+            if overflowed {
+                self.report(start, String::from("literal value does not fit in 64 bits"))?;
+            }
+            return Ok(Packet {
+                version: version,
+                type_id: type_id,
+                start: start,
+                end: self.bits.position(),
+                contents: PacketContents::Literal(value),
+            });
+        }
+        // Handle operator packet - sub-packets given by total length in bits or by number of packets
+        let mut sub_packets: Vec<Packet> = vec![];
+        let length_type = if self.read_bits(1)? == 0 {
+            let length = self.read_bits(15)? as usize;
+            let end = self.bits.position() + length;
+            while self.bits.position() < end {
+                sub_packets.push(self.decode_packet(depth + 1)?);
+            }
+            if self.bits.position() > end {
+                self.report(
+                    end,
+                    format!(
+                        "sub-packets overrun the declared length of {} bits by {} bits",
+                        length,
+                        self.bits.position() - end
+                    ),
+                )?;
+            }
+            LengthType::TotalBits(length)
+        } else {
+            let count = self.read_bits(11)? as usize;
+            for _ in 0..count {
+                sub_packets.push(self.decode_packet(depth + 1)?);
+            }
+            LengthType::PacketCount(count)
+        };
+        let packet = Packet {
+            version: version,
+            type_id: type_id,
+            start: start,
+            end: self.bits.position(),
+            contents: PacketContents::Operator {
+                length_type: length_type,
+                sub_packets: sub_packets,
+            },
+        };
+        let sub_packet_count = packet.sub_packets().len();
+        if (5..=7).contains(&type_id) && sub_packet_count != 2 {
+            self.report(
+                start,
+                format!(
+                    "{} packet has {} sub-packets, expected 2",
+                    packet.type_name(),
+                    sub_packet_count
+                ),
+            )?;
+        } else if (2..=3).contains(&type_id) && sub_packet_count == 0 {
+            self.report(
+                start,
+                format!("{} packet has no sub-packets", packet.type_name()),
+            )?;
+        }
+        return Ok(packet);
     }
-    *cursor += 3;
-    return u64::from_str_radix(&digits, 2).unwrap();
 }
 
+day_solution!(Day16, 16, Transmission);
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::fs::*;
+    use std::io::BufReader;
 
     #[test]
-    fn test_d16_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day16.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(947, result);
-    }
-
-    #[test]
-    fn test_d16_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day16.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(660797830937, result);
-    }
-
-    #[test]
-    fn test_d16_p1_test_001() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_001.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(6, result);
+    fn test_d16_streaming_matches_generator() {
+        for i in 1..=7 {
+            let path = format!("./input/2021/test/day_16_test_{:03}.txt", i);
+            let transmission = parse_input(&read_to_string(&path).unwrap()).unwrap();
+            // Tiny buffer so that the transmission is read in several chunks
+            let reader = BufReader::with_capacity(3, File::open(&path).unwrap());
+            let packet = decode_transmission(reader).unwrap();
+            assert_eq!(solve_part_1(&transmission), packet.version_sum());
+            assert_eq!(solve_part_2(&transmission), packet.evaluate().unwrap());
+        }
     }
 
     #[test]
-    fn test_d16_p1_test_002() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_002.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(9, result);
+    fn test_d16_bad_transmissions() {
+        let packet = decode_transmission("\n\nD2FE28\n".as_bytes()).unwrap();
+        assert_eq!(Ok(2021), packet.evaluate());
+        let error = decode_transmission("\nD2FE\n28".as_bytes()).unwrap_err();
+        assert_eq!(2, error.line);
+        assert!(decode_transmission("D2GE28".as_bytes()).is_err());
+        assert!(parse_input("D2GE28").is_err());
+        // Greater-than packet with a single literal sub-packet
+        assert!(decode_transmission("B6004408".as_bytes()).is_err());
+        // Whitespace is only allowed around the transmission, not inside it
+        let packet = decode_transmission("  D2FE28 \r\n".as_bytes()).unwrap();
+        assert_eq!(Ok(2021), packet.evaluate());
+        let error = decode_transmission("D2 FE28".as_bytes()).unwrap_err();
+        assert_eq!("bit 8: whitespace inside the transmission", error.message);
+        assert!(BitReader::new("FFFFFFFFFFFFFFFFFF".as_bytes())
+            .read_bits(65)
+            .is_err());
     }
 
     #[test]
-    fn test_d16_p1_test_003() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_003.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(14, result);
+    fn test_d16_nesting_limit() {
+        // Sum packets each holding the next, around a single literal packet
+        let nested = |depth: usize| {
+            let mut bits = "000000100000000001".repeat(depth - 1) + "00010000001";
+            bits.push_str(&"0".repeat((4 - bits.len() % 4) % 4));
+            (0..bits.len())
+                .step_by(4)
+                .map(|i| format!("{:X}", u8::from_str_radix(&bits[i..i + 4], 2).unwrap()))
+                .collect::<String>()
+        };
+        let packet = decode_transmission(nested(MAX_PACKET_DEPTH).as_bytes()).unwrap();
+        assert_eq!(Ok(1), packet.evaluate());
+        let error = decode_transmission(nested(MAX_PACKET_DEPTH + 1).as_bytes()).unwrap_err();
+        assert_eq!(
+            format!(
+                "bit {}: packets nested more than 64 deep",
+                MAX_PACKET_DEPTH * 18
+            ),
+            error.message
+        );
+        assert!(decode_transmission_lenient(nested(1000).as_bytes()).is_err());
     }
 
     #[test]
-    fn test_d16_p1_test_004() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_004.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(16, result);
+    fn test_d16_render_packet() {
+        // Sum of 1 and the product of 2 and 3
+        let packet = decode_transmission("020084082002C2084180".as_bytes()).unwrap();
+        assert_eq!(Ok(7), packet.evaluate());
+        assert_eq!("(+ 1 (* 2 3))", packet.render(PacketFormat::SExpression));
+        assert_eq!("(1 + (2 * 3))", packet.render(PacketFormat::Infix));
+        let tree = [
+            "v0 sum (type 0) bits 0..73, length type 1 (2 sub-packets)",
+            "  v0 literal (type 4) bits 18..29 = 1",
+            "  v0 product (type 1) bits 29..73, length type 0 (22 bits)",
+            "    v0 literal (type 4) bits 51..62 = 2",
+            "    v0 literal (type 4) bits 62..73 = 3",
+        ];
+        assert_eq!(tree.join("\n") + "\n", packet.render(PacketFormat::Tree));
+        let packet = decode_transmission("9C0141080250320F1802104A08".as_bytes()).unwrap();
+        assert_eq!("((1 + 3) = (2 * 2))", packet.render(PacketFormat::Infix));
+        assert_eq!(
+            "min(7, 8, 9)",
+            decode_transmission("880086C3E88112".as_bytes())
+                .unwrap()
+                .render(PacketFormat::Infix)
+        );
     }
 
     #[test]
-    fn test_d16_p1_test_005() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_005.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(12, result);
+    fn test_d16_evaluate_overflow() {
+        // Product of 2^32 and 2^32
+        let packet = decode_transmission("0600848C2108421000918421084200".as_bytes()).unwrap();
+        assert_eq!(
+            Err(String::from("product packet at bits 0..120 overflows u64")),
+            packet.evaluate()
+        );
     }
 
     #[test]
-    fn test_d16_p1_test_006() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_006.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(23, result);
+    fn test_d16_strict_decoding() {
+        // Minimum packet with no sub-packets
+        let error = decode_transmission("0A0000".as_bytes()).unwrap_err();
+        assert_eq!(
+            "line 1: bit 0: minimum packet has no sub-packets",
+            error.to_string()
+        );
+        // Sum packet declaring 5 bits of sub-packets, followed by an 11-bit literal
+        let error = decode_transmission("0000144080".as_bytes()).unwrap_err();
+        assert_eq!(
+            "bit 27: sub-packets overrun the declared length of 5 bits by 6 bits",
+            error.message
+        );
+        // Equal-to packet with three sub-packets
+        let error = decode_transmission("1E00C408811040".as_bytes()).unwrap_err();
+        assert_eq!(
+            "bit 0: equal-to packet has 3 sub-packets, expected 2",
+            error.message
+        );
+        // Literal 2^64 + 5
+        let error = decode_transmission("1230842108421084210840A0".as_bytes()).unwrap_err();
+        assert_eq!(
+            "bit 0: literal value does not fit in 64 bits",
+            error.message
+        );
+        // Literal 2021 followed by padding ending in a set bit
+        let error = decode_transmission("D2FE29".as_bytes()).unwrap_err();
+        assert_eq!(
+            "bit 23: non-zero padding after the outermost packet",
+            error.message
+        );
+        let error = decode_transmission("D2FE".as_bytes()).unwrap_err();
+        assert_eq!("transmission ended after 16 bits", error.message);
     }
 
     #[test]
-    fn test_d16_p1_test_007() {
-        let input = parse_input(&read_to_string("./input/2021/test/day_16_test_007.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(31, result);
+    fn test_d16_lenient_decoding() {
+        let (packet, warnings) = decode_transmission_lenient("D2FE29".as_bytes()).unwrap();
+        assert_eq!(Ok(2021), packet.evaluate());
+        assert_eq!(
+            vec![PacketWarning {
+                bit: 23,
+                message: String::from("non-zero padding after the outermost packet"),
+            }],
+            warnings
+        );
+        // Missing bits of the last literal group are read as 0
+        let (packet, warnings) = decode_transmission_lenient("D2FE".as_bytes()).unwrap();
+        assert_eq!(Ok(2016), packet.evaluate());
+        assert_eq!(1, warnings.len());
+        assert_eq!(16, warnings[0].bit);
+        let (packet, warnings) = decode_transmission_lenient("0A0000".as_bytes()).unwrap();
+        assert_eq!(
+            Err(String::from(
+                "minimum packet at bits 0..18 has no sub-packets"
+            )),
+            packet.evaluate()
+        );
+        assert_eq!(0, warnings[0].bit);
+        let (packet, warnings) = decode_transmission_lenient("1E00C408811040".as_bytes()).unwrap();
+        assert_eq!(3, packet.sub_packets().len());
+        assert_eq!(1, warnings.len());
+        assert!(packet.evaluate().is_err());
+        assert!(decode_transmission_lenient("D2GE28".as_bytes()).is_err());
+        let (packet, warnings) = decode_transmission_lenient("D2 FE28".as_bytes()).unwrap();
+        assert_eq!(Ok(2021), packet.evaluate());
+        assert_eq!(
+            vec![PacketWarning {
+                bit: 8,
+                message: String::from("whitespace inside the transmission"),
+            }],
+            warnings
+        );
     }
 }
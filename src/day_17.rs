@@ -1,8 +1,31 @@
-use std::collections::HashSet;
+use super::utils::map::Point2D;
+use super::utils::parse;
 
-use regex::Regex;
+/// Inclusive range of step counts, with no upper bound if the last step is None.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct StepRange {
+    first: u64,
+    last: Option<u64>,
+}
 
-struct TargetArea {
+impl StepRange {
+    pub fn new(first: u64, last: Option<u64>) -> Self {
+        Self {
+            first: first,
+            last: last,
+        }
+    }
+
+    /// Checks if the step range shares at least one step count with the other step range.
+    pub fn intersects(&self, other: &StepRange) -> bool {
+        let starts_before_other_ends = other.last.is_none_or(|last| self.first <= last);
+        let other_starts_before_end = self.last.is_none_or(|last| other.first <= last);
+        return starts_before_other_ends && other_starts_before_end;
+    }
+}
+
+/// Rectangular target area that the probe must be inside of after at least one step.
+pub struct TargetArea {
     x_min: i64,
     x_max: i64,
     y_min: i64,
@@ -18,120 +41,441 @@ impl TargetArea {
             y_max: y_max,
         }
     }
-}
 
-#[aoc_generator(day17)]
-fn parse_input(input: &str) -> TargetArea {
-    let input_regex = Regex::new(r"^target area: x=(-?\d+)..(-?\d+), y=(-?\d+)..(-?\d+)$").unwrap();
-    let input_line = input.lines().next().unwrap().trim();
-    let captures = input_regex.captures(input_line).unwrap();
-    let x_min = captures[1].parse::<i64>().unwrap();
-    let x_max = captures[2].parse::<i64>().unwrap();
-    let y_min = captures[3].parse::<i64>().unwrap();
-    let y_max = captures[4].parse::<i64>().unwrap();
-    return TargetArea::new(x_min, x_max, y_min, y_max);
-}
+    /// Determines the range of step counts at which the probe launched with the given initial
+    /// x-velocity is within the target area x-bounds. Drag brings the x-velocity to 0, after which
+    /// the x-position never changes, so the range has no upper bound if the probe stops in the
+    /// target area.
+    pub fn x_hit_steps(&self, x_vel: i64) -> Option<StepRange> {
+        if x_vel == 0 {
+            if self.contains_x(0) {
+                return Some(StepRange::new(1, None));
+            }
+            return None;
+        }
+        // Distance travelled from the origin increases until the probe stops
+        let stop_step = x_vel.unsigned_abs();
+        let dist = |step: u64| calculate_x_position(x_vel, step).abs();
+        let (near, far) = {
+            if x_vel > 0 {
+                (self.x_min, self.x_max)
+            } else {
+                (-self.x_max, -self.x_min)
+            }
+        };
+        let first = partition_point(1, stop_step + 1, |step| dist(step) < near);
+        if first > stop_step || dist(first) > far {
+            return None;
+        }
+        if self.contains_x(calculate_x_position(x_vel, stop_step)) {
+            return Some(StepRange::new(first, None));
+        }
+        let last = partition_point(first, stop_step + 1, |step| dist(step) <= far) - 1;
+        return Some(StepRange::new(first, Some(last)));
+    }
 
-#[aoc(day17, part1)]
-fn solve_part_1(target_area: &TargetArea) -> i64 {
-    let mut max_y_position = i64::MIN;
-    for y in -500..500 {
-        for x in -500..500 {
-            let mut pos = (0, 0);
-            let mut vel = (x, y);
-            let mut reached_target_area = false;
-            let mut current_max_y_pos = i64::MIN;
-            loop {
-                if pos.1 > current_max_y_pos {
-                    current_max_y_pos = pos.1;
-                }
-                // Check if initial conditions mean the probe misses the target area
-                if pos.0 > target_area.x_max || pos.1 < target_area.y_min {
-                    break;
-                }
-                // Update probe position and velocity
-                pos.0 += vel.0;
-                pos.1 += vel.1;
-                if vel.0 < 0 {
-                    vel.0 += 1;
-                } else if vel.0 > 0 {
-                    vel.0 -= 1;
+    /// Determines the ranges of step counts at which the probe launched with the given initial
+    /// y-velocity is within the target area y-bounds. There is at most one range while the probe
+    /// is rising and one while it is falling.
+    pub fn y_hit_steps(&self, y_vel: i64) -> Vec<StepRange> {
+        let mut output: Vec<StepRange> = vec![];
+        // Probe is rising (or level at the peak) up to and including this step
+        let peak_step = (y_vel + 1).max(0) as u64;
+        if peak_step >= 1 {
+            let first = partition_point(1, peak_step + 1, |step| {
+                calculate_y_position(y_vel, step) < self.y_min
+            });
+            let end = partition_point(first, peak_step + 1, |step| {
+                calculate_y_position(y_vel, step) <= self.y_max
+            });
+            if first < end {
+                output.push(StepRange::new(first, Some(end - 1)));
+            }
+        }
+        // Find a step count at which the falling probe is below the target area
+        let fall_start = peak_step + 1;
+        let mut fall_end = fall_start;
+        while calculate_y_position(y_vel, fall_end) >= self.y_min {
+            fall_end = fall_start + 2 * (fall_end - fall_start) + 1;
+        }
+        let first = partition_point(fall_start, fall_end, |step| {
+            calculate_y_position(y_vel, step) > self.y_max
+        });
+        let end = partition_point(first, fall_end, |step| {
+            calculate_y_position(y_vel, step) >= self.y_min
+        });
+        if first < end {
+            output.push(StepRange::new(first, Some(end - 1)));
+        }
+        return output;
+    }
+
+    /// Determines the inclusive x- and y-velocity bounds outside of which the probe cannot hit the
+    /// target area. Returns None if there is an infinite number of velocities that hit the target
+    /// area, which happens if the target area includes the launch height and the probe can come
+    /// to a stop above it.
+    pub fn velocity_bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        // Probe needs to be able to reach the near edge before drag stops it
+        let x_bounds = {
+            if self.x_min > 0 {
+                (calculate_min_triangular_root(self.x_min), self.x_max)
+            } else if self.x_max < 0 {
+                (self.x_min, -calculate_min_triangular_root(-self.x_max))
+            } else {
+                (self.x_min, self.x_max)
+            }
+        };
+        // Probe returns to launch height with speed one more than its initial upward velocity
+        let y_bounds = {
+            if self.y_max < 0 {
+                (self.y_min, -self.y_min - 1)
+            } else if self.y_min > 0 {
+                (calculate_min_triangular_root(self.y_min), self.y_max)
+            } else {
+                // Probe is within y-bounds whenever it returns to launch height, so the upper bound
+                // is set by the last step at which the probe can be within the x-bounds
+                let mut last_x_step = 1;
+                for x_vel in x_bounds.0..=x_bounds.1 {
+                    if let Some(x_steps) = self.x_hit_steps(x_vel) {
+                        last_x_step = last_x_step.max(x_steps.last?);
+                    }
                 }
-                vel.1 -= 1;
-                // Check if probe is in the target area
-                if pos.0 >= target_area.x_min
-                    && pos.0 <= target_area.x_max
-                    && pos.1 >= target_area.y_min
-                    && pos.1 <= target_area.y_max
+                (self.y_min, self.y_max.max(last_x_step as i64))
+            }
+        };
+        return Some((x_bounds, y_bounds));
+    }
+
+    /// Determines all initial velocities for which the probe hits the target area. Returns None if
+    /// there is an infinite number of such velocities.
+    pub fn find_hit_velocities(&self) -> Option<Vec<(i64, i64)>> {
+        let ((x_vel_min, x_vel_max), (y_vel_min, y_vel_max)) = self.velocity_bounds()?;
+        let mut output: Vec<(i64, i64)> = vec![];
+        for x_vel in x_vel_min..=x_vel_max {
+            let x_steps = match self.x_hit_steps(x_vel) {
+                Some(steps) => steps,
+                None => continue,
+            };
+            for y_vel in y_vel_min..=y_vel_max {
+                if self
+                    .y_hit_steps(y_vel)
+                    .iter()
+                    .any(|y_steps| y_steps.intersects(&x_steps))
                 {
-                    reached_target_area = true;
-                    break;
+                    output.push((x_vel, y_vel));
                 }
             }
-            if reached_target_area && current_max_y_pos > max_y_position {
-                max_y_position = current_max_y_pos;
+        }
+        return Some(output);
+    }
+
+    /// Checks if the x-value is within the target area x-bounds.
+    fn contains_x(&self, x: i64) -> bool {
+        return x >= self.x_min && x <= self.x_max;
+    }
+}
+
+/// Result of launching the probe at the target area.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum ProbeOutcome {
+    /// Probe was inside the target area after the given step. The maximum height is the peak of the
+    /// whole trajectory, which the probe may not reach until after the hit if it is still rising.
+    Hit { step: u64, max_height: i64 },
+    /// Probe went past the far side of the target area x-bounds.
+    Overshot,
+    /// Probe stopped moving horizontally before reaching the target area x-bounds.
+    Undershot,
+    /// Probe dropped below the target area while within its x-bounds.
+    FellThrough,
+}
+
+/// Iterator over the trajectory of a probe launched at the target area. Yields the step count,
+/// position and velocity for the launch and each step after, up to and including the step at which
+/// the probe is known to have hit or missed the target area.
+struct Probe<'a> {
+    target_area: &'a TargetArea,
+    step: u64,
+    position: Point2D,
+    velocity: Point2D,
+    max_height: i64,
+    launched: bool,
+    outcome: Option<ProbeOutcome>,
+}
+
+impl<'a> Probe<'a> {
+    /// Launches a new probe from the origin with the given initial velocity.
+    pub fn launch(target_area: &'a TargetArea, x_vel: i64, y_vel: i64) -> Self {
+        Self {
+            target_area: target_area,
+            step: 0,
+            position: Point2D::new(0, 0),
+            velocity: Point2D::new(x_vel, y_vel),
+            max_height: 0,
+            launched: false,
+            outcome: None,
+        }
+    }
+
+    /// Gets the outcome of the launch, if it has been determined yet.
+    pub fn outcome(&self) -> Option<ProbeOutcome> {
+        return self.outcome;
+    }
+
+    /// Steps the probe until it has hit or missed the target area, returning the outcome.
+    pub fn run(mut self) -> ProbeOutcome {
+        while self.next().is_some() {}
+        return self.outcome().unwrap();
+    }
+
+    /// Checks the current state of the probe for a hit or a certain miss.
+    fn check_outcome(&self) -> Option<ProbeOutcome> {
+        let target_area = self.target_area;
+        let x = self.position.get_x();
+        let y = self.position.get_y();
+        let x_vel = self.velocity.get_x();
+        let x_in_bounds = x >= target_area.x_min && x <= target_area.x_max;
+        if self.step > 0 && x_in_bounds && y >= target_area.y_min && y <= target_area.y_max {
+            let peak = y + calculate_peak_height(self.velocity.get_y());
+            return Some(ProbeOutcome::Hit {
+                step: self.step,
+                max_height: self.max_height.max(peak),
+            });
+        }
+        // Probe is short of the target area if it is still on the same side as the launch point
+        let x_short = (x < target_area.x_min && target_area.x_min > 0)
+            || (x > target_area.x_max && target_area.x_max < 0);
+        // Probe is falling below the target area and will never come back up
+        if y < target_area.y_min && self.velocity.get_y() < 0 {
+            if x_in_bounds {
+                return Some(ProbeOutcome::FellThrough);
+            } else if x_short {
+                return Some(ProbeOutcome::Undershot);
             }
+            return Some(ProbeOutcome::Overshot);
         }
+        // Probe is outside the x-bounds and is not moving towards them
+        if !x_in_bounds {
+            let moving_towards =
+                (x < target_area.x_min && x_vel > 0) || (x > target_area.x_max && x_vel < 0);
+            if !moving_towards {
+                if x_short {
+                    return Some(ProbeOutcome::Undershot);
+                }
+                return Some(ProbeOutcome::Overshot);
+            }
+        }
+        return None;
     }
-    return max_y_position;
+}
+
+impl Iterator for Probe<'_> {
+    type Item = (u64, Point2D, Point2D);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.outcome.is_some() {
+            return None;
+        }
+        // Report the launch state before taking the first step
+        if !self.launched {
+            self.launched = true;
+        } else {
+            self.step += 1;
+            // Drag pulls x-velocity towards zero while gravity reduces y-velocity
+            let drag = Point2D::new(-self.velocity.get_x().signum(), -1);
+            self.position = self.position + self.velocity;
+            self.velocity = self.velocity + drag;
+            self.max_height = self.max_height.max(self.position.get_y());
+        }
+        self.outcome = self.check_outcome();
+        return Some((self.step, self.position, self.velocity));
+    }
+}
+
+#[aoc_generator(day17)]
+fn parse_input(input: &str) -> Result<TargetArea, parse::ParseError> {
+    let input_regex = cached_regex!(r"^target area: x=(-?\d+)..(-?\d+), y=(-?\d+)..(-?\d+)$");
+    let (line_num, line) = parse::first_line(input)?;
+    let captures = parse::captures(input_regex, line_num, line)?;
+    let x_min = parse::value::<i64>(line_num, &captures[1])?;
+    let x_max = parse::value::<i64>(line_num, &captures[2])?;
+    let y_min = parse::value::<i64>(line_num, &captures[3])?;
+    let y_max = parse::value::<i64>(line_num, &captures[4])?;
+    return Ok(TargetArea::new(x_min, x_max, y_min, y_max));
+}
+
+#[aoc(day17, part1)]
+fn solve_part_1(target_area: &TargetArea) -> i64 {
+    // Highest point is the greatest peak of any trajectory that hits the target area
+    return target_area
+        .find_hit_velocities()
+        .expect("Day 17 - infinite number of initial velocities hit the target area")
+        .iter()
+        .map(
+            |(x_vel, y_vel)| match Probe::launch(target_area, *x_vel, *y_vel).run() {
+                ProbeOutcome::Hit { max_height, .. } => max_height,
+                outcome => panic!("Day 17 - probe expected to hit target area: {:?}", outcome),
+            },
+        )
+        .max()
+        .expect("Day 17 - no initial velocity hits the target area");
 }
 
 #[aoc(day17, part2)]
 fn solve_part_2(target_area: &TargetArea) -> usize {
-    let mut good_vels: HashSet::<(i64, i64)> = HashSet::new();
-    for y in -500..500 {
-        for x in -500..500 {
-            let mut pos = (0, 0);
-            let mut vel = (x, y);
-            let mut reached_target_area = false;
-            loop {
-                // Check if initial conditions mean the probe misses the target area
-                if pos.0 > target_area.x_max || pos.1 < target_area.y_min {
-                    break;
-                }
-                // Update probe position and velocity
-                pos.0 += vel.0;
-                pos.1 += vel.1;
-                if vel.0 < 0 {
-                    vel.0 += 1;
-                } else if vel.0 > 0 {
-                    vel.0 -= 1;
-                }
-                vel.1 -= 1;
-                // Check if probe is in the target area
-                if pos.0 >= target_area.x_min
-                    && pos.0 <= target_area.x_max
-                    && pos.1 >= target_area.y_min
-                    && pos.1 <= target_area.y_max
-                {
-                    reached_target_area = true;
-                    break;
-                }
-            }
-            if reached_target_area {
-                good_vels.insert((x, y));
-            }
+    return target_area
+        .find_hit_velocities()
+        .expect("Day 17 - infinite number of initial velocities hit the target area")
+        .len();
+}
+
+/// Calculates the height gained by the probe from a point at which it has the given y-velocity.
+/// Gravity reduces the upward speed by 1 each step, so a probe moving upwards rises by
+/// `y_vel + (y_vel - 1) + ... + 1` before falling.
+fn calculate_peak_height(y_vel: i64) -> i64 {
+    if y_vel <= 0 {
+        return 0;
+    }
+    return y_vel * (y_vel + 1) / 2;
+}
+
+/// Calculates the x-position of the probe after the given number of steps. Drag reduces the speed
+/// of the probe by 1 each step until it stops.
+fn calculate_x_position(x_vel: i64, step: u64) -> i64 {
+    let moving_steps = step.min(x_vel.unsigned_abs()) as i64;
+    let dist = moving_steps * x_vel.abs() - moving_steps * (moving_steps - 1) / 2;
+    return dist * x_vel.signum();
+}
+
+/// Calculates the y-position of the probe after the given number of steps. Gravity reduces the
+/// y-velocity by 1 each step.
+fn calculate_y_position(y_vel: i64, step: u64) -> i64 {
+    let step = step as i64;
+    return step * y_vel - step * (step - 1) / 2;
+}
+
+/// Calculates the smallest n for which the nth triangular number is at least the given value.
+fn calculate_min_triangular_root(value: i64) -> i64 {
+    let mut n = ((((8 * value + 1) as f64).sqrt() - 1.0) / 2.0).floor() as i64;
+    while n * (n + 1) / 2 < value {
+        n += 1;
+    }
+    while n > 0 && (n - 1) * n / 2 >= value {
+        n -= 1;
+    }
+    return n;
+}
+
+/// Finds the first step in the range [start, end) for which the predicate is false, given that the
+/// predicate is true for all steps before that step and false for all steps after. Returns end if
+/// the predicate is true for all steps in the range.
+fn partition_point<F: Fn(u64) -> bool>(start: u64, end: u64, pred: F) -> u64 {
+    let mut low = start;
+    let mut high = end;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
         }
     }
-    return good_vels.len();
+    return low;
 }
 
+day_solution!(Day17, 17, TargetArea);
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs::*;
 
     #[test]
-    fn test_d17_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day17.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(7875, result);
+    fn test_d17_target_left_of_origin() {
+        let target_area = TargetArea::new(-30, -20, -10, -5);
+        assert_eq!(45, solve_part_1(&target_area));
+        assert_eq!(112, solve_part_2(&target_area));
+    }
+
+    #[test]
+    fn test_d17_analytic_matches_simulation() {
+        let target_areas = vec![
+            TargetArea::new(20, 30, -10, -5),
+            TargetArea::new(-30, -20, -10, -5),
+            TargetArea::new(20, 30, 5, 10),
+            TargetArea::new(-3, 4, -10, -5),
+            TargetArea::new(7, 9, -4, 3),
+        ];
+        for target_area in target_areas {
+            let mut expected: Vec<(i64, i64)> = vec![];
+            for x_vel in -40..=40 {
+                for y_vel in -40..=40 {
+                    let outcome = Probe::launch(&target_area, x_vel, y_vel).run();
+                    if let ProbeOutcome::Hit { .. } = outcome {
+                        expected.push((x_vel, y_vel));
+                    }
+                }
+            }
+            assert_eq!(Some(expected), target_area.find_hit_velocities());
+        }
+    }
+
+    #[test]
+    fn test_d17_target_above_origin() {
+        // Probe launched at (6, 10) rises to 55 and hits the target on its way back down
+        let target_area = TargetArea::new(20, 30, 5, 10);
+        assert_eq!(55, solve_part_1(&target_area));
+        // Probe hits on its first step while still rising towards its peak
+        let hit = ProbeOutcome::Hit {
+            step: 1,
+            max_height: 55,
+        };
+        assert_eq!(hit, Probe::launch(&target_area, 25, 10).run());
+        assert_eq!(0, calculate_peak_height(-3));
+    }
+
+    #[test]
+    fn test_d17_infinite_hit_velocities() {
+        let target_area = TargetArea::new(5, 8, -2, 2);
+        assert_eq!(None, target_area.find_hit_velocities());
+    }
+
+    #[test]
+    fn test_d17_probe_outcomes() {
+        let target_area = TargetArea::new(20, 30, -10, -5);
+        let hit = ProbeOutcome::Hit {
+            step: 7,
+            max_height: 3,
+        };
+        assert_eq!(hit, Probe::launch(&target_area, 7, 2).run());
+        assert_eq!(
+            ProbeOutcome::Overshot,
+            Probe::launch(&target_area, 17, -4).run()
+        );
+        assert_eq!(
+            ProbeOutcome::Undershot,
+            Probe::launch(&target_area, 5, 0).run()
+        );
+        assert_eq!(
+            ProbeOutcome::FellThrough,
+            Probe::launch(&target_area, 6, 10).run()
+        );
     }
 
     #[test]
-    fn test_d17_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day17.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(2321, result);
+    fn test_d17_probe_trajectory() {
+        let target_area = TargetArea::new(20, 30, -10, -5);
+        let mut probe = Probe::launch(&target_area, 6, 3);
+        let trajectory = probe.by_ref().collect::<Vec<(u64, Point2D, Point2D)>>();
+        assert_eq!((0, Point2D::new(0, 0), Point2D::new(6, 3)), trajectory[0]);
+        assert_eq!((1, Point2D::new(6, 3), Point2D::new(5, 2)), trajectory[1]);
+        assert_eq!(
+            (9, Point2D::new(21, -9), Point2D::new(0, -6)),
+            trajectory[9]
+        );
+        assert_eq!(10, trajectory.len());
+        let hit = ProbeOutcome::Hit {
+            step: 9,
+            max_height: 6,
+        };
+        assert_eq!(Some(hit), probe.outcome());
     }
 }
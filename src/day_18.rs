@@ -1,18 +1,12 @@
 use std::collections::HashMap;
 
-use regex::Regex;
+use super::utils::parse;
 
 #[aoc_generator(day18)]
-fn parse_input(input: &str) -> Vec<String> {
-    let mut snailfish_numbers: Vec<String> = vec![];
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        snailfish_numbers.push(line.to_string());
-    }
-    return snailfish_numbers;
+fn parse_input(input: &str) -> Result<Vec<String>, parse::ParseError> {
+    return Ok(parse::lines(input)
+        .map(|(_, line)| line.to_string())
+        .collect::<Vec<String>>());
 }
 
 #[aoc(day18, part1)]
@@ -160,8 +154,8 @@ fn add_snailfish_numbers(left: &String, right: &String) -> String {
 
 /// Calculates the magnitude of the given snailfish number.
 fn calculate_snailfish_number_magnitude(snailfish_number: &String) -> u64 {
-    let pair_regex = Regex::new(r"(\[\d+,\d+\])").unwrap();
-    let pair_value_extract_regex = Regex::new(r"(\d+),(\d+)").unwrap();
+    let pair_regex = cached_regex!(r"(\[\d+,\d+\])");
+    let pair_value_extract_regex = cached_regex!(r"(\d+),(\d+)");
     let mut magnitude_sum = snailfish_number.to_string();
     loop {
         // Find all pairs that have not been converted to single value
@@ -224,22 +218,4 @@ fn tokenise_snailfish_number(snailfish_number: &String) -> Vec<String> {
     return output;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d18_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day18.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(4435, result);
-    }
-
-    #[test]
-    fn test_d18_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day18.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(4802, result);
-    }
-}
+day_solution!(Day18, 18, Vec<String>);
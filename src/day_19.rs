@@ -2,37 +2,30 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
 
+use super::utils::parse;
+
 #[aoc_generator(day19)]
-fn parse_input(input: &str) -> Vec<Vec<(i64, i64, i64)>> {
+fn parse_input(input: &str) -> Result<Vec<Vec<(i64, i64, i64)>>, parse::ParseError> {
     let mut scanner_data: Vec<Vec<(i64, i64, i64)>> = vec![];
-    let mut input_lines = input.lines();
-    loop {
+    let header_regex = cached_regex!(r"^--- scanner \d+ ---$");
+    for block in parse::blocks(input) {
         // Process next scanner data input
-        let scanner_header = input_lines.next();
-        if scanner_header.is_none() {
-            break;
-        }
+        let (header_line_num, header) = block[0];
+        parse::captures(header_regex, header_line_num, header)?;
         let mut beacons: Vec<(i64, i64, i64)> = vec![];
-        loop {
-            let next = {
-                let candidate = input_lines.next();
-                if candidate.is_none() {
-                    break;
-                }
-                candidate.unwrap().trim()
-            };
-            if next.is_empty() {
-                break;
+        for (line_num, line) in block.iter().skip(1) {
+            let beacon_pos = parse::separated::<i64>(*line_num, line, ",")?;
+            if beacon_pos.len() != 3 {
+                return Err(parse::ParseError::new(
+                    *line_num,
+                    &format!("expected 3 beacon co-ordinates, got {}", beacon_pos.len()),
+                ));
             }
-            let beacon_pos = next
-                .split(",")
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
             beacons.push((beacon_pos[0], beacon_pos[1], beacon_pos[2]));
         }
         scanner_data.push(beacons);
     }
-    return scanner_data;
+    return Ok(scanner_data);
 }
 
 #[aoc(day19, part1)]
@@ -272,22 +265,4 @@ fn rot90_z_axis(input: &(i64, i64, i64)) -> (i64, i64, i64) {
     return (-input.1, input.0, input.2);
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d19_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day19.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(459, result);
-    }
-
-    #[test]
-    fn test_d19_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day19.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(19130, result);
-    }
-}
+day_solution!(Day19, 19, Vec<Vec<(i64, i64, i64)>>);
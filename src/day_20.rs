@@ -1,35 +1,38 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
+use super::simulation::Simulation;
+use super::utils::parse;
+use super::utils::render::*;
+
 #[aoc_generator(day20)]
-fn parse_input(input: &str) -> (Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64)) {
-    let mut input_lines = input.lines();
+fn parse_input(
+    input: &str,
+) -> Result<(Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64)), parse::ParseError> {
+    let blocks = parse::blocks(input);
+    if blocks.len() != 2 || blocks[0].len() != 1 {
+        return Err(parse::ParseError::new(
+            1,
+            "expected enhancement algorithm and input image separated by a blank line",
+        ));
+    }
+    // Pixels are recorded as '1' (light) or '0' (dark)
+    let to_pixel = |c: char| match c {
+        '#' => Some('1'),
+        '.' => Some('0'),
+        _ => None,
+    };
     // Read in the input enhancement algorithm
-    let mut img_enhance_alg_raw = input_lines.next().unwrap().trim().to_string();
-    img_enhance_alg_raw = img_enhance_alg_raw.replace(".", "0");
-    img_enhance_alg_raw = img_enhance_alg_raw.replace("#", "1");
-    let img_enhance_alg = img_enhance_alg_raw.chars().collect::<Vec<char>>();
-    input_lines.next();
+    let img_enhance_alg = parse::char_grid(&blocks[0], to_pixel)?.remove(0);
     // Read in the input image
     let mut input_image: HashMap<(i64, i64), char> = HashMap::new();
-    let mut x = 0;
-    let mut y = 0;
-    loop {
-        let mut line = {
-            let candidate = input_lines.next();
-            if candidate.is_none() {
-                break;
-            }
-            candidate.unwrap().trim().to_string()
-        };
-        line = line.replace(".", "0");
-        line = line.replace("#", "1");
-        x = 0;
-        for c in line.chars() {
-            input_image.insert((x, y), c);
-            x += 1;
+    let image = parse::char_grid(&blocks[1], to_pixel)?;
+    let y = image.len() as i64;
+    let x = image[0].len() as i64;
+    for (y, row) in image.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            input_image.insert((x as i64, y as i64), *c);
         }
-        y += 1;
     }
     // Pad out extra three layers around input image
     let mut x_min = -3;
@@ -50,51 +53,101 @@ fn parse_input(input: &str) -> (Vec<char>, HashMap<(i64, i64), char>, (i64, i64)
         y_min += 1;
         y_max -= 1;
     }
-    return (img_enhance_alg, input_image, (-3, -3), (x + 2, y + 2));
+    return Ok((img_enhance_alg, input_image, (-3, -3), (x + 2, y + 2)));
 }
 
 #[aoc(day20, part1)]
 fn solve_part_1(
     image_input: &(Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64)),
 ) -> usize {
-    let img_enhance_alg = image_input.0.clone();
-    let mut input_image = image_input.1.clone();
-    let (mut x_min, mut y_min) = image_input.2;
-    let (mut y_max, mut x_max) = image_input.3;
+    let mut enhancement = ImageEnhancement::new(image_input);
     // Apply 2 iterations of image enhancement
     for _ in 0..2 {
-        input_image = apply_image_enhancement(
-            &input_image,
-            &img_enhance_alg,
-            &mut x_min,
-            &mut x_max,
-            &mut y_min,
-            &mut y_max,
-        );
+        enhancement.step();
     }
-    return input_image.values().filter(|x| **x == '1').count();
+    return enhancement.lit_pixel_count();
 }
 
 #[aoc(day20, part2)]
 fn solve_part_2(
     image_input: &(Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64)),
 ) -> usize {
-    let img_enhance_alg = image_input.0.clone();
-    let mut input_image = image_input.1.clone();
-    let (mut x_min, mut y_min) = image_input.2;
-    let (mut y_max, mut x_max) = image_input.3;
+    let mut enhancement = ImageEnhancement::new(image_input);
     // Apply 50 iterations of image enhancement
     for _ in 0..50 {
-        input_image = apply_image_enhancement(
-            &input_image,
-            &img_enhance_alg,
-            &mut x_min,
-            &mut x_max,
-            &mut y_min,
-            &mut y_max,
+        enhancement.step();
+    }
+    return enhancement.lit_pixel_count();
+}
+
+/// Image that has one round of image enhancement applied on each step of the simulation.
+pub struct ImageEnhancement {
+    img_enhance_alg: Vec<char>,
+    image: HashMap<(i64, i64), char>,
+    x_min: i64,
+    x_max: i64,
+    y_min: i64,
+    y_max: i64,
+}
+
+impl ImageEnhancement {
+    /// Creates a new image enhancement from the generated algorithm, padded image and its bounds.
+    fn new(image_input: &(Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64))) -> Self {
+        let (x_min, y_min) = image_input.2;
+        let (x_max, y_max) = image_input.3;
+        Self {
+            img_enhance_alg: image_input.0.clone(),
+            image: image_input.1.clone(),
+            x_min: x_min,
+            x_max: x_max,
+            y_min: y_min,
+            y_max: y_max,
+        }
+    }
+
+    /// Creates a new image enhancement from the algorithm and input image in the puzzle input.
+    pub fn from_input(input: &str) -> Result<Self, parse::ParseError> {
+        return Ok(ImageEnhancement::new(&parse_input(input)?));
+    }
+
+    /// Gets the pixels of the image, as '1' (light) or '0' (dark), indexed by [y][x] from the
+    /// top-left of the recorded area.
+    pub fn pixels(&self) -> Vec<Vec<char>> {
+        return (self.y_min..=self.y_max)
+            .map(|y| {
+                (self.x_min..=self.x_max)
+                    .map(|x| self.image[&(x, y)])
+                    .collect::<Vec<char>>()
+            })
+            .collect::<Vec<Vec<char>>>();
+    }
+
+    /// Gets the number of light pixels in the recorded area of the image.
+    pub fn lit_pixel_count(&self) -> usize {
+        return self.image.values().filter(|x| **x == '1').count();
+    }
+}
+
+impl Simulation for ImageEnhancement {
+    fn step(&mut self) {
+        self.image = apply_image_enhancement(
+            &self.image,
+            &self.img_enhance_alg,
+            &mut self.x_min,
+            &mut self.x_max,
+            &mut self.y_min,
+            &mut self.y_max,
         );
     }
-    return input_image.values().filter(|x| **x == '1').count();
+
+    /// Draws the recorded area of the image, with light pixels in white and dark pixels in black.
+    fn snapshot(&self) -> GridImage {
+        return GridImage::from_grid(&self.pixels(), |c| match c {
+            '1' => Colour::WHITE,
+            _ => Colour::BLACK,
+        })
+        .unwrap();
+    }
 }
 
 /// Applies single round of image enhancement to the input image. An additional layer of default
@@ -164,22 +217,8 @@ fn apply_image_enhancement(
     return output_image;
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d20_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day20.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(5268, result);
-    }
-
-    #[test]
-    fn test_d20_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day20.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(16875, result);
-    }
-}
+day_solution!(
+    Day20,
+    20,
+    (Vec<char>, HashMap<(i64, i64), char>, (i64, i64), (i64, i64))
+);
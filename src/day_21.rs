@@ -1,193 +1,512 @@
-/// Represents the 100-sided deterministic die used in AOC 2021 Day 21.
-struct DeterministicDie {
+use std::collections::HashMap;
+
+use super::utils::parse;
+
+/// Number of spaces on the board in AOC 2021 Day 21.
+const BOARD_SIZE: u64 = 10;
+
+/// Common behaviour for dice that can be used to play the deterministic dice game.
+pub trait Die {
+    /// Rolls the die once, returning the value rolled.
+    fn roll(&mut self) -> u64;
+
+    /// Gets the number of times the die has been rolled.
+    fn times_rolled(&self) -> u64;
+}
+
+/// Represents the deterministic die used in AOC 2021 Day 21, which always rolls 1 then 2 and so on
+/// up to its number of sides before starting again from 1.
+pub struct DeterministicDie {
+    sides: u64,
     times_rolled: u64,
     next_roll: u64,
 }
 
 impl DeterministicDie {
-    /// Creates a new DeterministicDie with starting roll of 1.
-    pub fn new() -> Self {
-        Self {
+    /// Creates a new DeterministicDie with starting roll of 1. Returns an error if the die has no
+    /// sides.
+    pub fn new(sides: u64) -> Result<Self, String> {
+        if sides == 0 {
+            return Err(String::from("die must have at least one side"));
+        }
+        return Ok(Self {
+            sides: sides,
             times_rolled: 0,
             next_roll: 1,
-        }
+        });
     }
+}
 
+impl Die for DeterministicDie {
     /// Rolls the die and rotates the next roll value.
-    pub fn roll(&mut self) -> u64 {
+    fn roll(&mut self) -> u64 {
         self.times_rolled += 1;
         let this_roll = self.next_roll;
         self.next_roll += 1;
-        if self.next_roll > 100 {
+        if self.next_roll > self.sides {
             self.next_roll = 1;
         }
         return this_roll;
     }
+
+    fn times_rolled(&self) -> u64 {
+        return self.times_rolled;
+    }
 }
 
-/// Represents the state of universe required to play successive turns in the Dirac Dice game.
-#[derive(Copy, Clone)]
-struct UniverseState {
-    p1_pos: u64,
-    p2_pos: u64,
-    p1_score: u64,
-    p2_score: u64,
-    universe_mult: u64,
+/// Die that rolls pseudo-random values, generated using xorshift from the given seed so that games
+/// can be repeated.
+pub struct SeededDie {
+    sides: u64,
+    times_rolled: u64,
+    state: u64,
 }
 
-impl UniverseState {
-    pub fn new(p1_pos: u64, p2_pos: u64) -> Self {
-        Self {
-            p1_pos: p1_pos,
-            p2_pos: p2_pos,
-            p1_score: 0,
-            p2_score: 0,
-            universe_mult: 1,
+impl SeededDie {
+    /// Creates a new SeededDie. A seed of 0 is replaced with a fixed non-zero value, since the
+    /// xorshift state must never be 0. Returns an error if the die has no sides.
+    pub fn new(sides: u64, seed: u64) -> Result<Self, String> {
+        if sides == 0 {
+            return Err(String::from("die must have at least one side"));
         }
+        return Ok(Self {
+            sides: sides,
+            times_rolled: 0,
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        });
     }
 }
 
-#[aoc_generator(day21)]
-fn parse_input(input: &str) -> (u64, u64) {
-    let mut input_lines = input.lines();
-    let p1_start = input_lines
-        .next()
-        .unwrap()
-        .trim()
-        .split(": ")
-        .collect::<Vec<&str>>()[1]
-        .parse::<u64>()
-        .unwrap();
-    let p2_start = input_lines
-        .next()
-        .unwrap()
-        .trim()
-        .split(": ")
-        .collect::<Vec<&str>>()[1]
-        .parse::<u64>()
-        .unwrap();
-    return (p1_start, p2_start);
+impl Die for SeededDie {
+    fn roll(&mut self) -> u64 {
+        self.times_rolled += 1;
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state % self.sides + 1;
+    }
+
+    fn times_rolled(&self) -> u64 {
+        return self.times_rolled;
+    }
 }
 
-#[aoc(day21, part1)]
-fn solve_part_1(player_start_pos: &(u64, u64)) -> u64 {
-    let (mut p1_pos, mut p2_pos) = player_start_pos;
-    let mut p1_score = 0;
-    let mut p2_score = 0;
-    let mut det_die = DeterministicDie::new();
-    loop {
-        // Player 1 turn
-        let mut p1_rolls = 0;
-        for _ in 0..3 {
-            p1_rolls += det_die.roll();
-        }
-        p1_pos += p1_rolls % 10;
-        if p1_pos > 10 {
-            p1_pos = p1_pos - 10;
+/// Record of a single turn taken in the deterministic dice game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TurnEvent {
+    pub turn: u64,
+    pub player: usize,
+    pub rolls: Vec<u64>,
+    pub position: u64,
+    pub score: u64,
+}
+
+/// Deterministic dice game played by any number of players, who take turns to roll the die and
+/// move around the circular board until one player reaches the target score.
+pub struct Game<D: Die> {
+    board_size: u64,
+    target_score: u64,
+    rolls_per_turn: u64,
+    positions: Vec<u64>,
+    scores: Vec<u64>,
+    die: D,
+    events: Vec<TurnEvent>,
+    winner: Option<usize>,
+}
+
+impl<D: Die> Game<D> {
+    /// Creates a new game with a player starting at each of the given positions. Returns an error
+    /// if there are no players, the board has no spaces or a start position is not on the board.
+    pub fn new(
+        start_positions: &[u64],
+        board_size: u64,
+        target_score: u64,
+        rolls_per_turn: u64,
+        die: D,
+    ) -> Result<Self, String> {
+        check_start_positions(start_positions, board_size, None)?;
+        return Ok(Self {
+            board_size: board_size,
+            target_score: target_score,
+            rolls_per_turn: rolls_per_turn,
+            positions: start_positions.to_vec(),
+            scores: vec![0; start_positions.len()],
+            die: die,
+            events: vec![],
+            winner: None,
+        });
+    }
+
+    /// Plays the next turn of the game. Returns the event for the turn, or None if the game has
+    /// already been won.
+    pub fn play_turn(&mut self) -> Option<&TurnEvent> {
+        if self.winner.is_some() {
+            return None;
         }
-        p1_score += p1_pos;
-        if p1_score >= 1000 {
-            break;
+        let turn = self.events.len() as u64;
+        let player = (turn % self.positions.len() as u64) as usize;
+        let rolls = (0..self.rolls_per_turn)
+            .map(|_| self.die.roll())
+            .collect::<Vec<u64>>();
+        self.positions[player] = advance_position(
+            self.positions[player],
+            rolls.iter().sum::<u64>(),
+            self.board_size,
+        );
+        self.scores[player] += self.positions[player];
+        if self.scores[player] >= self.target_score {
+            self.winner = Some(player);
         }
-        // Player 2 turn
-        let mut p2_rolls = 0;
-        for _ in 0..3 {
-            p2_rolls += det_die.roll();
+        self.events.push(TurnEvent {
+            turn: turn,
+            player: player,
+            rolls: rolls,
+            position: self.positions[player],
+            score: self.scores[player],
+        });
+        return self.events.last();
+    }
+
+    /// Plays turns until a player reaches the target score. Returns the index of the winner.
+    pub fn play(&mut self) -> usize {
+        while self.play_turn().is_some() {}
+        return self.winner.unwrap();
+    }
+
+    /// Gets the log of all turns played so far.
+    pub fn events(&self) -> &[TurnEvent] {
+        return &self.events;
+    }
+
+    /// Gets the current score for each player.
+    pub fn scores(&self) -> &[u64] {
+        return &self.scores;
+    }
+
+    /// Gets the die used to play the game.
+    pub fn die(&self) -> &D {
+        return &self.die;
+    }
+}
+
+/// Configurable rules for the Dirac Dice game.
+#[derive(Copy, Clone, Debug)]
+struct DiracRules {
+    board_size: u64,
+    die_faces: u64,
+    rolls_per_turn: u64,
+    target_score: u64,
+    players: usize,
+}
+
+impl DiracRules {
+    /// Creates new rules for the Dirac Dice game. Returns an error if the board or the die has no
+    /// spaces or faces, or if there are no players.
+    pub fn new(
+        board_size: u64,
+        die_faces: u64,
+        rolls_per_turn: u64,
+        target_score: u64,
+        players: usize,
+    ) -> Result<Self, String> {
+        if board_size == 0 || die_faces == 0 {
+            return Err(String::from(
+                "board and die must have at least one space and face",
+            ));
         }
-        p2_pos += p2_rolls % 10;
-        if p2_pos > 10 {
-            p2_pos = p2_pos - 10;
+        if players == 0 {
+            return Err(String::from("game must have at least one player"));
         }
-        p2_score += p2_pos;
-        if p2_score >= 1000 {
-            break;
+        return Ok(Self {
+            board_size: board_size,
+            die_faces: die_faces,
+            rolls_per_turn: rolls_per_turn,
+            target_score: target_score,
+            players: players,
+        });
+    }
+
+    /// Creates the rules given in AOC 2021 Day 21 Part 2 - two players on a board of size 10 each
+    /// roll a 3-sided die three times per turn, playing until one player has 21 points.
+    pub fn standard() -> Self {
+        Self::new(BOARD_SIZE, 3, 3, 21, 2).unwrap()
+    }
+
+    /// Calculates the number of universes created for each possible total of the dice rolled in a
+    /// single turn. Output is a list of (roll total, universe count) pairs.
+    pub fn roll_distribution(&self) -> Vec<(u64, u64)> {
+        // Number of ways to reach each total, indexed by total
+        let mut counts: Vec<u64> = vec![1];
+        for _ in 0..self.rolls_per_turn {
+            let mut new_counts: Vec<u64> = vec![0; counts.len() + self.die_faces as usize];
+            for (total, count) in counts.iter().enumerate() {
+                for face in 1..=self.die_faces as usize {
+                    new_counts[total + face] += count;
+                }
+            }
+            counts = new_counts;
         }
+        return counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(total, count)| (total as u64, *count))
+            .collect::<Vec<(u64, u64)>>();
     }
-    if p1_score < p2_score {
-        return p1_score * det_die.times_rolled;
-    } else {
-        return p2_score * det_die.times_rolled;
+
+    /// Calculates the board position reached by moving forward from the given position.
+    pub fn advance(&self, pos: u64, spaces: u64) -> u64 {
+        return advance_position(pos, spaces, self.board_size);
     }
 }
 
-#[aoc(day21, part2)]
-fn solve_part_2(player_start_pos: &(u64, u64)) -> u64 {
-    let universe_state = UniverseState::new(player_start_pos.0, player_start_pos.1);
-    // Generate possible roll values and counts for possible combos of three Dirac dice rolls
-    let roll_val_counts: Vec<(u64, u64)> =
-        vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
-    let (p1_wins, p2_wins) = play_turn_dirac(&universe_state, &roll_val_counts);
-    if p1_wins > p2_wins {
-        return p1_wins;
-    } else {
-        return p2_wins;
-    }
-}
-
-/// Plays a turn of the Dirac dice game, with input universe split as required after Player 1 and
-/// Player 2 turns.
-fn play_turn_dirac(universe_0: &UniverseState, roll_val_counts: &Vec<(u64, u64)>) -> (u64, u64) {
-    let mut p1_wins = 0;
-    let mut p2_wins = 0;
-    // P1 turns
-    let mut candidate_universes: Vec<UniverseState> = vec![];
-    for (roll_val, count) in roll_val_counts {
-        let mut universe_state_split = universe_0.clone();
-        universe_state_split.p1_pos += roll_val;
-        universe_state_split.universe_mult *= count;
-        if universe_state_split.p1_pos > 10 {
-            universe_state_split.p1_pos = universe_state_split.p1_pos - 10;
-        }
-        universe_state_split.p1_score += universe_state_split.p1_pos;
-        if universe_state_split.p1_score >= 21 {
-            p1_wins += universe_state_split.universe_mult;
-        } else {
-            candidate_universes.push(universe_state_split);
+/// Represents the state of a universe in the Dirac Dice game at the start of a player's turn.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct DiracState {
+    positions: Vec<u64>,
+    scores: Vec<u64>,
+    to_move: usize,
+}
+
+impl DiracState {
+    /// Creates the state at the start of the game. Returns an error if the number of start
+    /// positions does not match the number of players in the rules, or a start position is not on
+    /// the board.
+    pub fn new(start_positions: &[u64], rules: &DiracRules) -> Result<Self, String> {
+        check_start_positions(start_positions, rules.board_size, Some(rules.players))?;
+        return Ok(Self {
+            positions: start_positions.to_vec(),
+            scores: vec![0; start_positions.len()],
+            to_move: 0,
+        });
+    }
+}
+
+/// Memoised solver for the Dirac Dice game, counting the universes in which each player wins.
+struct DiracSolver {
+    rules: DiracRules,
+    roll_distribution: Vec<(u64, u64)>,
+    memo: HashMap<DiracState, Vec<u64>>,
+}
+
+impl DiracSolver {
+    pub fn new(rules: DiracRules) -> Self {
+        Self {
+            rules: rules,
+            roll_distribution: rules.roll_distribution(),
+            memo: HashMap::new(),
         }
     }
-    // P2 turns
-    let mut new_candidate_universes: Vec<UniverseState> = vec![];
-    for universe in candidate_universes {
-        for (roll_val, count) in roll_val_counts {
-            let mut universe_state_split = universe.clone();
-            universe_state_split.p2_pos += roll_val;
-            universe_state_split.universe_mult *= count;
-            if universe_state_split.p2_pos > 10 {
-                universe_state_split.p2_pos = universe_state_split.p2_pos - 10;
+
+    /// Counts the number of universes in which each player wins, starting from the given state.
+    /// Returns an error if the number of universes does not fit in a u64.
+    pub fn count_wins(&mut self, state: &DiracState) -> Result<Vec<u64>, String> {
+        if let Some(wins) = self.memo.get(state) {
+            return Ok(wins.clone());
+        }
+        let overflow = || String::from("number of universes overflows u64");
+        let mut wins = vec![0u64; self.rules.players];
+        let player = state.to_move;
+        for (roll_total, count) in self.roll_distribution.clone() {
+            let mut new_state = state.clone();
+            new_state.positions[player] = self.rules.advance(state.positions[player], roll_total);
+            new_state.scores[player] += new_state.positions[player];
+            if new_state.scores[player] >= self.rules.target_score {
+                wins[player] = wins[player].checked_add(count).ok_or_else(overflow)?;
+                continue;
             }
-            universe_state_split.p2_score += universe_state_split.p2_pos;
-            if universe_state_split.p2_score >= 21 {
-                p2_wins += universe_state_split.universe_mult;
-            } else {
-                new_candidate_universes.push(universe_state_split);
+            new_state.to_move = (player + 1) % self.rules.players;
+            let sub_wins = self.count_wins(&new_state)?;
+            for (total, sub_total) in wins.iter_mut().zip(sub_wins) {
+                *total = count
+                    .checked_mul(sub_total)
+                    .and_then(|universes| total.checked_add(universes))
+                    .ok_or_else(overflow)?;
             }
         }
+        self.memo.insert(state.clone(), wins.clone());
+        return Ok(wins);
+    }
+}
+
+#[aoc_generator(day21)]
+fn parse_input(input: &str) -> Result<Vec<u64>, parse::ParseError> {
+    let mut output: Vec<u64> = vec![];
+    let start_regex = cached_regex!(r"^Player \d+ starting position: (\d+)$");
+    for (line_num, line) in parse::lines(input) {
+        let captures = parse::captures(start_regex, line_num, line)?;
+        let position = parse::value::<u64>(line_num, &captures[1])?;
+        if !(1..=BOARD_SIZE).contains(&position) {
+            return Err(parse::ParseError::new(
+                line_num,
+                &format!(
+                    "starting position must be from 1 to {}: {}",
+                    BOARD_SIZE, position
+                ),
+            ));
+        }
+        output.push(position);
+    }
+    if output.is_empty() {
+        return Err(parse::ParseError::new(1, "input has no players"));
+    }
+    return Ok(output);
+}
+
+#[aoc(day21, part1)]
+fn solve_part_1(player_start_pos: &[u64]) -> u64 {
+    let die = DeterministicDie::new(100).unwrap();
+    let mut game = Game::new(player_start_pos, BOARD_SIZE, 1000, 3, die)
+        .unwrap_or_else(|e| panic!("Day 21 - {}", e));
+    game.play();
+    let losing_score = game.scores().iter().min().unwrap();
+    return losing_score * game.die().times_rolled();
+}
+
+#[aoc(day21, part2)]
+fn solve_part_2(player_start_pos: &[u64]) -> u64 {
+    let rules = DiracRules::standard();
+    let state =
+        DiracState::new(player_start_pos, &rules).unwrap_or_else(|e| panic!("Day 21 - {}", e));
+    let wins = DiracSolver::new(rules)
+        .count_wins(&state)
+        .unwrap_or_else(|e| panic!("Day 21 - {}", e));
+    return *wins.iter().max().unwrap();
+}
+
+/// Checks that there is at least one player (or exactly the expected number of players, if given)
+/// and that each start position is on a board with spaces numbered 1 up to the board size.
+fn check_start_positions(
+    start_positions: &[u64],
+    board_size: u64,
+    expected_players: Option<usize>,
+) -> Result<(), String> {
+    if start_positions.is_empty() {
+        return Err(String::from("game must have at least one player"));
     }
-    // Keep playing for all universes without a winner yet
-    for universe in new_candidate_universes {
-        let (sub_p1_wins, sub_p2_wins) = play_turn_dirac(&universe, roll_val_counts);
-        p1_wins += sub_p1_wins;
-        p2_wins += sub_p2_wins;
+    if let Some(players) = expected_players.filter(|p| *p != start_positions.len()) {
+        return Err(format!(
+            "game has {} players but {} start positions were given",
+            players,
+            start_positions.len()
+        ));
     }
-    return (p1_wins, p2_wins);
+    if let Some(pos) = start_positions
+        .iter()
+        .find(|pos| !(1..=board_size).contains(*pos))
+    {
+        return Err(format!(
+            "start position {} is not on a board of size {}",
+            pos, board_size
+        ));
+    }
+    return Ok(());
+}
+
+/// Calculates the position reached by moving forward the given number of spaces from the given
+/// position on a circular board with spaces numbered 1 up to the board size.
+fn advance_position(pos: u64, spaces: u64, board_size: u64) -> u64 {
+    return (pos - 1 + spaces) % board_size + 1;
 }
 
+day_solution!(Day21, 21, Vec<u64>);
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::fs::*;
 
     #[test]
-    fn test_d21_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day21.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(920079, result);
+    fn test_d21_dirac_wins_test_001() {
+        let input =
+            parse_input(&read_to_string("./input/2021/test/day_21_test_001.txt").unwrap()).unwrap();
+        let rules = DiracRules::standard();
+        let state = DiracState::new(&input, &rules).unwrap();
+        let wins = DiracSolver::new(rules).count_wins(&state).unwrap();
+        assert_eq!(vec![444356092776315, 341960390180808], wins);
+    }
+
+    #[test]
+    fn test_d21_roll_distribution() {
+        let expected = vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+        assert_eq!(expected, DiracRules::standard().roll_distribution());
+        let expected = vec![(2, 1), (3, 2), (4, 3), (5, 4), (6, 3), (7, 2), (8, 1)];
+        assert_eq!(
+            expected,
+            DiracRules::new(10, 4, 2, 21, 2)
+                .unwrap()
+                .roll_distribution()
+        );
+    }
+
+    #[test]
+    fn test_d21_game_event_log() {
+        let mut game =
+            Game::new(&[4, 8], 10, 1000, 3, DeterministicDie::new(100).unwrap()).unwrap();
+        game.play_turn();
+        game.play_turn();
+        let expected = vec![
+            TurnEvent {
+                turn: 0,
+                player: 0,
+                rolls: vec![1, 2, 3],
+                position: 10,
+                score: 10,
+            },
+            TurnEvent {
+                turn: 1,
+                player: 1,
+                rolls: vec![4, 5, 6],
+                position: 3,
+                score: 3,
+            },
+        ];
+        assert_eq!(expected, game.events());
+        assert_eq!(0, game.play());
+        assert_eq!(331, game.events().len() as u64);
+        assert!(game.play_turn().is_none());
+    }
+
+    #[test]
+    fn test_d21_game_multi_player_seeded_die() {
+        let mut game = Game::new(&[1, 4, 7], 10, 50, 3, SeededDie::new(6, 2021).unwrap()).unwrap();
+        let winner = game.play();
+        assert!(game.scores()[winner] >= 50);
+        assert_eq!(game.events().len() as u64 * 3, game.die().times_rolled());
+        for event in game.events() {
+            assert_eq!(event.turn as usize % 3, event.player);
+            assert!(event.rolls.iter().all(|roll| (1..=6).contains(roll)));
+        }
+    }
+
+    #[test]
+    fn test_d21_invalid_games() {
+        assert!(DeterministicDie::new(0).is_err());
+        assert!(SeededDie::new(0, 2021).is_err());
+        assert!(DiracRules::new(0, 3, 3, 21, 2).is_err());
+        assert!(DiracRules::new(10, 3, 3, 21, 0).is_err());
+        assert!(DiracState::new(&[4, 8, 1], &DiracRules::standard()).is_err());
+        let die = || DeterministicDie::new(100).unwrap();
+        assert!(Game::new(&[], 10, 1000, 3, die()).is_err());
+        assert!(Game::new(&[4, 0], 10, 1000, 3, die()).is_err());
+        assert!(Game::new(&[4, 11], 10, 1000, 3, die()).is_err());
+        assert!(DiracState::new(&[0], &DiracRules::standard()).is_err());
+        assert!(parse_input("").is_err());
+        assert!(parse_input("Player 1 starting position: 0\n").is_err());
+        assert!(parse_input("Player 1 starting position: 11\n").is_err());
     }
 
     #[test]
-    fn test_d21_p2_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day21.txt").unwrap());
-        let result = solve_part_2(&input);
-        assert_eq!(56852759190649, result);
+    fn test_d21_dirac_wins_overflow() {
+        let rules = DiracRules::new(10, 3, 3, 40, 2).unwrap();
+        let state = DiracState::new(&[4, 8], &rules).unwrap();
+        assert_eq!(
+            Err(String::from("number of universes overflows u64")),
+            DiracSolver::new(rules).count_wins(&state)
+        );
     }
 }
@@ -1,35 +1,30 @@
 use std::collections::HashSet;
 
-use regex::Regex;
+use super::utils::parse;
 
 #[aoc_generator(day22)]
-fn parse_input(input: &str) -> Vec<(bool, i64, i64, i64, i64, i64, i64)> {
+fn parse_input(
+    input: &str,
+) -> Result<Vec<(bool, i64, i64, i64, i64, i64, i64)>, parse::ParseError> {
     let mut reboot_commands: Vec<(bool, i64, i64, i64, i64, i64, i64)> = vec![];
     let line_regex =
-        Regex::new(r"^(on|off) x=(-?\d+)..(-?\d+),y=(-?\d+)..(-?\d+),z=(-?\d+)..(-?\d+)$").unwrap();
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let captures = line_regex.captures(line).unwrap();
-        let on_state = {
-            if &captures[1] == "on" {
-                true
-            } else {
-                false
-            }
-        };
-        let x_min = captures[2].parse::<i64>().unwrap();
-        let x_max = captures[3].parse::<i64>().unwrap();
-        let y_min = captures[4].parse::<i64>().unwrap();
-        let y_max = captures[5].parse::<i64>().unwrap();
-        let z_min = captures[6].parse::<i64>().unwrap();
-        let z_max = captures[7].parse::<i64>().unwrap();
-        let cmd = (on_state, x_min, x_max, y_min, y_max, z_min, z_max);
+        cached_regex!(r"^(on|off) x=(-?\d+)..(-?\d+),y=(-?\d+)..(-?\d+),z=(-?\d+)..(-?\d+)$");
+    for (line_num, line) in parse::lines(input) {
+        let captures = parse::captures(line_regex, line_num, line)?;
+        let on_state = &captures[1] == "on";
+        let bound = |i: usize| parse::value::<i64>(line_num, &captures[i]);
+        let cmd = (
+            on_state,
+            bound(2)?,
+            bound(3)?,
+            bound(4)?,
+            bound(5)?,
+            bound(6)?,
+            bound(7)?,
+        );
         reboot_commands.push(cmd);
     }
-    return reboot_commands;
+    return Ok(reboot_commands);
 }
 
 #[aoc(day22, part1)]
@@ -57,15 +52,4 @@ fn solve_part_1(reboot_commands: &Vec<(bool, i64, i64, i64, i64, i64, i64)>) ->
     return reactor_cubes_on.len();
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fs::*;
-
-    #[test]
-    fn test_d22_p1_actual() {
-        let input = parse_input(&read_to_string("./input/2021/day22.txt").unwrap());
-        let result = solve_part_1(&input);
-        assert_eq!(615700, result);
-    }
-}
+day_solution!(Day22, 22, Vec<(bool, i64, i64, i64, i64, i64, i64)>, part1);
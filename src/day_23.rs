@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use priority_queue::DoublePriorityQueue;
+
+use super::utils::map::get_surrounding_points_no_diagonals;
+use super::utils::parse;
+
+/// Extra room rows inserted into the burrow diagram when it is unfolded for Part 2.
+const UNFOLDED_ROWS: [&str; 2] = ["  #D#C#B#A#", "  #D#B#A#C#"];
+
+/// The four types of amphipod, each of which belongs in its own room.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Amphipod {
+    Amber,
+    Bronze,
+    Copper,
+    Desert,
+}
+
+impl Amphipod {
+    /// Converts the character used in the burrow diagram into an amphipod.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'A' => Some(Amphipod::Amber),
+            'B' => Some(Amphipod::Bronze),
+            'C' => Some(Amphipod::Copper),
+            'D' => Some(Amphipod::Desert),
+            _ => None,
+        }
+    }
+
+    /// Gets the energy required for the amphipod to move a single step.
+    pub fn energy(&self) -> u64 {
+        match self {
+            Amphipod::Amber => 1,
+            Amphipod::Bronze => 10,
+            Amphipod::Copper => 100,
+            Amphipod::Desert => 1000,
+        }
+    }
+
+    /// Gets the index of the room the amphipod belongs in, with rooms ordered from left to right.
+    pub fn target_room(&self) -> usize {
+        match self {
+            Amphipod::Amber => 0,
+            Amphipod::Bronze => 1,
+            Amphipod::Copper => 2,
+            Amphipod::Desert => 3,
+        }
+    }
+}
+
+/// Location of a single space in the burrow. Hallway spaces are indexed from left to right, and
+/// room spaces by the room index and depth (with 0 being the space next to the hallway).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Location {
+    Hallway(usize),
+    Room(usize, usize),
+}
+
+/// Locations of all amphipods in the burrow.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct BurrowState {
+    hallway: Vec<Option<Amphipod>>,
+    rooms: Vec<Vec<Option<Amphipod>>>,
+}
+
+impl BurrowState {
+    /// Gets the amphipod at the given location, if there is one.
+    pub fn get(&self, location: &Location) -> Option<Amphipod> {
+        match location {
+            Location::Hallway(i) => self.hallway[*i],
+            Location::Room(room, depth) => self.rooms[*room][*depth],
+        }
+    }
+
+    /// Updates the contents of the given location.
+    pub fn set(&mut self, location: &Location, amphipod: Option<Amphipod>) {
+        match location {
+            Location::Hallway(i) => self.hallway[*i] = amphipod,
+            Location::Room(room, depth) => self.rooms[*room][*depth] = amphipod,
+        }
+    }
+
+    /// Checks if every room is full of the amphipods that belong in it.
+    pub fn is_organised(&self) -> bool {
+        return self.rooms.iter().enumerate().all(|(room, spaces)| {
+            spaces
+                .iter()
+                .all(|space| space.is_some_and(|a| a.target_room() == room))
+        });
+    }
+
+    /// Checks if the room contains only amphipods that belong in it (or no amphipods at all).
+    fn room_is_settled(&self, room: usize) -> bool {
+        return self.rooms[room]
+            .iter()
+            .flatten()
+            .all(|a| a.target_room() == room);
+    }
+}
+
+/// Layout of the burrow, along with the starting locations of the amphipods. The path between each
+/// room space and each hallway space is determined from the burrow diagram when it is parsed.
+pub struct Burrow {
+    grid: Vec<Vec<char>>,
+    initial_state: BurrowState,
+    hallway_stops: Vec<usize>,
+    paths: HashMap<(Location, Location), Vec<Location>>,
+}
+
+impl Burrow {
+    /// Creates a new Burrow by parsing the given burrow diagram, with each row of the grid being a
+    /// line of the diagram. Returns an error if the diagram does not have a hallway above four
+    /// rooms of the same depth, if each type of amphipod does not fill exactly one room, or if a
+    /// room is not connected to the hallway.
+    pub fn new(grid: Vec<Vec<char>>) -> Result<Self, parse::ParseError> {
+        if grid.len() < 3 {
+            return Err(parse::ParseError::new(
+                grid.len() + 1,
+                "expected burrow with hallway and rooms",
+            ));
+        }
+        let is_open = |c: char| c == '.' || Amphipod::from_char(c).is_some();
+        // Hallway is the open spaces in the row below the top wall
+        let hallway_xs = (0..grid[1].len())
+            .filter(|x| is_open(grid[1][*x]))
+            .collect::<Vec<usize>>();
+        if hallway_xs.is_empty() {
+            return Err(parse::ParseError::new(2, "expected hallway in burrow"));
+        }
+        // Rooms are the open columns below the hallway, ordered from left to right
+        let room_xs = (0..grid[2].len())
+            .filter(|x| is_open(grid[2][*x]))
+            .collect::<Vec<usize>>();
+        if room_xs.len() != 4 {
+            return Err(parse::ParseError::new(
+                3,
+                &format!("expected 4 rooms in burrow but found {}", room_xs.len()),
+            ));
+        }
+        let mut locations: HashMap<(usize, usize), Location> = HashMap::new();
+        let mut initial_state = BurrowState {
+            hallway: vec![],
+            rooms: vec![vec![]; room_xs.len()],
+        };
+        for (i, x) in hallway_xs.iter().enumerate() {
+            locations.insert((*x, 1), Location::Hallway(i));
+            initial_state.hallway.push(Amphipod::from_char(grid[1][*x]));
+        }
+        for (room, x) in room_xs.iter().enumerate() {
+            let mut y = 2;
+            while y < grid.len() && is_open(grid[y][*x]) {
+                locations.insert((*x, y), Location::Room(room, y - 2));
+                initial_state.rooms[room].push(Amphipod::from_char(grid[y][*x]));
+                y += 1;
+            }
+            if initial_state.rooms[room].len() != initial_state.rooms[0].len() {
+                return Err(parse::ParseError::new(
+                    y + 1,
+                    &format!("room {} is not the same depth as the first room", room + 1),
+                ));
+            }
+        }
+        let room_depth = initial_state.rooms[0].len();
+        for c in ['A', 'B', 'C', 'D'] {
+            let count = grid.iter().flatten().filter(|g| **g == c).count();
+            if count != room_depth {
+                return Err(parse::ParseError::new(
+                    3,
+                    &format!(
+                        "expected {} amphipods of type {} but found {}",
+                        room_depth, c, count
+                    ),
+                ));
+            }
+        }
+        // Amphipods never stop on the hallway space immediately outside a room
+        let hallway_stops = hallway_xs
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| !room_xs.contains(x))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        let mut paths: HashMap<(Location, Location), Vec<Location>> = HashMap::new();
+        for (coords, location) in locations.iter() {
+            if let Location::Room(..) = location {
+                let room_paths = find_grid_paths(&grid, &locations, *coords);
+                for stop in hallway_stops.iter() {
+                    let hallway = Location::Hallway(*stop);
+                    let path = match room_paths.get(&hallway) {
+                        Some(path) => path.clone(),
+                        None => {
+                            return Err(parse::ParseError::new(
+                                coords.1 + 1,
+                                "room is not connected to the hallway",
+                            ))
+                        }
+                    };
+                    let mut reversed = path.clone();
+                    reversed.reverse();
+                    paths.insert((*location, hallway), path);
+                    paths.insert((hallway, *location), reversed);
+                }
+            }
+        }
+        return Ok(Self {
+            grid: grid,
+            initial_state: initial_state,
+            hallway_stops: hallway_stops,
+            paths: paths,
+        });
+    }
+
+    /// Creates the unfolded version of the burrow, with the extra room rows inserted below the
+    /// first row of the rooms. Returns an error if the extra rows do not line up with the rooms.
+    pub fn unfold(&self) -> Result<Burrow, parse::ParseError> {
+        let mut grid = self.grid.clone();
+        for (i, row) in UNFOLDED_ROWS.iter().enumerate() {
+            grid.insert(3 + i, pad_row(row, grid[0].len()));
+        }
+        return Burrow::new(grid);
+    }
+
+    /// Finds the least total energy required to organise the amphipods into their rooms, using
+    /// Dijkstra's algorithm over the burrow states. Returns None if the amphipods cannot be
+    /// organised.
+    pub fn find_least_energy(&self) -> Option<u64> {
+        let mut energies: HashMap<BurrowState, u64> = HashMap::new();
+        energies.insert(self.initial_state.clone(), 0);
+        let mut visit_queue: DoublePriorityQueue<BurrowState, u64> = DoublePriorityQueue::new();
+        visit_queue.push(self.initial_state.clone(), 0);
+        while let Some((state, energy)) = visit_queue.pop_min() {
+            if state.is_organised() {
+                return Some(energy);
+            }
+            for (next_state, move_energy) in self.find_moves(&state) {
+                let new_energy = energy + move_energy;
+                let old_energy = energies.entry(next_state.clone()).or_insert(u64::MAX);
+                if new_energy < *old_energy {
+                    *old_energy = new_energy;
+                    visit_queue.push(next_state, new_energy);
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Finds all valid moves from the given state. Amphipods in the hallway can only move into their
+    /// own room if it contains no other types of amphipod, and amphipods in a room can only move
+    /// out to a hallway space if they or an amphipod below them do not belong in the room.
+    fn find_moves(&self, state: &BurrowState) -> Vec<(BurrowState, u64)> {
+        let mut output: Vec<(BurrowState, u64)> = vec![];
+        // Moves from the hallway into the destination room
+        for stop in self.hallway_stops.iter() {
+            let start = Location::Hallway(*stop);
+            let amphipod = match state.get(&start) {
+                Some(amphipod) => amphipod,
+                None => continue,
+            };
+            let room = amphipod.target_room();
+            if !state.room_is_settled(room) {
+                continue;
+            }
+            let depth = state.rooms[room].iter().rposition(|s| s.is_none()).unwrap();
+            if let Some(new_state) = self.try_move(state, start, Location::Room(room, depth)) {
+                output.push(new_state);
+            }
+        }
+        // Moves from the top of a room out to the hallway
+        for room in 0..state.rooms.len() {
+            if state.room_is_settled(room) {
+                continue;
+            }
+            let depth = state.rooms[room].iter().position(|s| s.is_some()).unwrap();
+            for stop in self.hallway_stops.iter() {
+                let start = Location::Room(room, depth);
+                if let Some(new_state) = self.try_move(state, start, Location::Hallway(*stop)) {
+                    output.push(new_state);
+                }
+            }
+        }
+        return output;
+    }
+
+    /// Moves the amphipod at the start location to the end location, if the path between them is
+    /// clear. Returns the resulting state and the energy used.
+    fn try_move(
+        &self,
+        state: &BurrowState,
+        start: Location,
+        end: Location,
+    ) -> Option<(BurrowState, u64)> {
+        let path = self.paths.get(&(start, end)).unwrap();
+        if path
+            .iter()
+            .skip(1)
+            .any(|location| state.get(location).is_some())
+        {
+            return None;
+        }
+        let amphipod = state.get(&start).unwrap();
+        let mut new_state = state.clone();
+        new_state.set(&start, None);
+        new_state.set(&end, Some(amphipod));
+        let energy = (path.len() as u64 - 1) * amphipod.energy();
+        return Some((new_state, energy));
+    }
+}
+
+#[aoc_generator(day23)]
+fn parse_input(input: &str) -> Result<Burrow, parse::ParseError> {
+    // Leading whitespace is kept, since it positions the rooms below the hallway. Blank lines are
+    // only dropped from the end, so that each row of the grid is at its line number less one.
+    let mut lines = input
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let grid = lines
+        .iter()
+        .map(|line| pad_row(line, width))
+        .collect::<Vec<Vec<char>>>();
+    return Burrow::new(grid);
+}
+
+#[aoc(day23, part1)]
+fn solve_part_1(burrow: &Burrow) -> u64 {
+    return burrow
+        .find_least_energy()
+        .unwrap_or_else(|| panic!("Day 23 - amphipods cannot be organised"));
+}
+
+#[aoc(day23, part2)]
+fn solve_part_2(burrow: &Burrow) -> u64 {
+    return burrow
+        .unfold()
+        .unwrap_or_else(|e| panic!("Day 23 - failed to unfold burrow: {}", e))
+        .find_least_energy()
+        .unwrap_or_else(|| panic!("Day 23 - amphipods cannot be organised"));
+}
+
+/// Converts the row of the burrow diagram into characters, padded with spaces to the given width.
+fn pad_row(row: &str, width: usize) -> Vec<char> {
+    let mut output = row.chars().collect::<Vec<char>>();
+    output.resize(width, ' ');
+    return output;
+}
+
+/// Finds the shortest path through the open spaces of the burrow grid from the start coordinates
+/// to every other location, using a breadth-first search. Each path includes both the start and
+/// end locations.
+fn find_grid_paths(
+    grid: &[Vec<char>],
+    locations: &HashMap<(usize, usize), Location>,
+    start: (usize, usize),
+) -> HashMap<Location, Vec<Location>> {
+    let mut previous: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut visit_queue: VecDeque<(usize, usize)> = VecDeque::new();
+    visit_queue.push_back(start);
+    previous.insert(start, start);
+    while let Some(current) = visit_queue.pop_front() {
+        let neighbours = get_surrounding_points_no_diagonals(
+            current.0,
+            current.1,
+            grid[0].len() - 1,
+            grid.len() - 1,
+        );
+        for neighbour in neighbours {
+            if !locations.contains_key(&neighbour) || previous.contains_key(&neighbour) {
+                continue;
+            }
+            previous.insert(neighbour, current);
+            visit_queue.push_back(neighbour);
+        }
+    }
+    // Trace the path back to the start from each location reached
+    let mut output: HashMap<Location, Vec<Location>> = HashMap::new();
+    for (coords, location) in locations.iter() {
+        if !previous.contains_key(coords) {
+            continue;
+        }
+        let mut path = vec![*location];
+        let mut current = *coords;
+        while current != start {
+            current = *previous.get(&current).unwrap();
+            path.push(*locations.get(&current).unwrap());
+        }
+        path.reverse();
+        output.insert(*location, path);
+    }
+    return output;
+}
+
+day_solution!(Day23, 23, Burrow);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::*;
+
+    #[test]
+    fn test_d23_example_layout() {
+        let input = read_to_string("./input/2021/test/day_23_test_001.txt").unwrap();
+        let burrow = parse_input(&input).unwrap();
+        assert_eq!(11, burrow.initial_state.hallway.len());
+        assert_eq!(7, burrow.hallway_stops.len());
+        assert_eq!(
+            vec![Some(Amphipod::Bronze), Some(Amphipod::Amber)],
+            burrow.initial_state.rooms[0]
+        );
+        assert!(!burrow.initial_state.is_organised());
+        assert_eq!(Some(12521), burrow.find_least_energy());
+        let unfolded = burrow.unfold().unwrap();
+        assert_eq!(4, unfolded.initial_state.rooms[3].len());
+    }
+
+    #[test]
+    fn test_d23_malformed_layout() {
+        let three_rooms = "#########\n#.......#\n###B#C#A###\n  #A#B#C#\n  #######\n";
+        assert_eq!(
+            Err(parse::ParseError::new(
+                3,
+                "expected 4 rooms in burrow but found 3"
+            )),
+            parse_input(three_rooms).map(|_| ())
+        );
+        let extra_amber = "#############\n#A..........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########\n";
+        assert!(parse_input(extra_amber).is_err());
+        assert!(parse_input("#############\n#...........#\n").is_err());
+    }
+}
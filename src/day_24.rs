@@ -0,0 +1,414 @@
+use super::utils::parse;
+
+/// Number of digits in a MONAD model number.
+const MODEL_NUMBER_DIGITS: usize = 14;
+
+/// Instructions making up each of the MONAD digit-processing blocks. Each block differs only in
+/// the divisor in the fifth instruction ("D") and the literals added in the sixth ("A") and
+/// sixteenth ("B") instructions.
+const MONAD_BLOCK_TEMPLATE: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z D", "add x A", "eql x w", "eql x 0",
+    "mul y 0", "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y B",
+    "mul y x", "add z y",
+];
+
+/// The four registers of the ALU.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Register {
+    /// Converts the register name used in the ALU program into a register.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "w" => Some(Register::W),
+            "x" => Some(Register::X),
+            "y" => Some(Register::Y),
+            "z" => Some(Register::Z),
+            _ => None,
+        }
+    }
+
+    /// Gets the index of the register in the ALU register file.
+    pub fn index(&self) -> usize {
+        match self {
+            Register::W => 0,
+            Register::X => 1,
+            Register::Y => 2,
+            Register::Z => 3,
+        }
+    }
+}
+
+/// Second operand of an ALU instruction - either a register or a literal value.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Operand {
+    Register(Register),
+    Literal(i64),
+}
+
+/// The six instructions supported by the ALU.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    Inp(Register),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Div(Register, Operand),
+    Mod(Register, Operand),
+    Eql(Register, Operand),
+}
+
+/// Arithmetic logic unit with four integer registers, all starting at 0.
+struct Alu {
+    registers: [i64; 4],
+}
+
+impl Alu {
+    pub fn new() -> Self {
+        Self { registers: [0; 4] }
+    }
+
+    /// Gets the current value held in the given register.
+    pub fn get(&self, register: Register) -> i64 {
+        return self.registers[register.index()];
+    }
+
+    /// Executes a single instruction, reading the next input value if required. Returns an error if
+    /// the instruction is invalid for the current register values or there is no input remaining.
+    /// Arithmetic is checked, so a result that does not fit in an i64 is an error rather than
+    /// wrapping around.
+    pub fn step<I: Iterator<Item = i64>>(
+        &mut self,
+        instruction: &Instruction,
+        inputs: &mut I,
+    ) -> Result<(), String> {
+        match instruction {
+            Instruction::Inp(a) => {
+                let value = inputs
+                    .next()
+                    .ok_or_else(|| String::from("Day 24 - no input value left for inp"))?;
+                self.registers[a.index()] = value;
+            }
+            Instruction::Add(a, b) => {
+                let (x, y) = (self.get(*a), self.resolve(b));
+                self.registers[a.index()] = x.checked_add(y).ok_or_else(|| {
+                    format!(
+                        "Day 24 - add instruction overflows with a={} and b={}",
+                        x, y
+                    )
+                })?;
+            }
+            Instruction::Mul(a, b) => {
+                let (x, y) = (self.get(*a), self.resolve(b));
+                self.registers[a.index()] = x.checked_mul(y).ok_or_else(|| {
+                    format!(
+                        "Day 24 - mul instruction overflows with a={} and b={}",
+                        x, y
+                    )
+                })?;
+            }
+            Instruction::Div(a, b) => {
+                let divisor = self.resolve(b);
+                if divisor == 0 {
+                    return Err(String::from("Day 24 - div instruction with b=0"));
+                }
+                let dividend = self.get(*a);
+                self.registers[a.index()] = dividend.checked_div(divisor).ok_or_else(|| {
+                    format!(
+                        "Day 24 - div instruction overflows with a={} and b={}",
+                        dividend, divisor
+                    )
+                })?;
+            }
+            Instruction::Mod(a, b) => {
+                let dividend = self.get(*a);
+                let divisor = self.resolve(b);
+                if dividend < 0 || divisor <= 0 {
+                    return Err(format!(
+                        "Day 24 - mod instruction with a={} and b={}",
+                        dividend, divisor
+                    ));
+                }
+                self.registers[a.index()] = dividend % divisor;
+            }
+            Instruction::Eql(a, b) => {
+                self.registers[a.index()] = (self.get(*a) == self.resolve(b)) as i64;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Executes all instructions in the program in order, using the given input values.
+    pub fn run<I: Iterator<Item = i64>>(
+        &mut self,
+        program: &[Instruction],
+        inputs: I,
+    ) -> Result<(), String> {
+        let mut inputs = inputs;
+        for instruction in program {
+            self.step(instruction, &mut inputs)?;
+        }
+        return Ok(());
+    }
+
+    /// Gets the value of the operand for the current register values.
+    fn resolve(&self, operand: &Operand) -> i64 {
+        match operand {
+            Operand::Register(register) => self.get(*register),
+            Operand::Literal(value) => *value,
+        }
+    }
+}
+
+/// Constraint between two digits of a valid model number, derived from a matching pair of MONAD
+/// blocks - the digit at the pop index equals the digit at the push index plus the offset.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct DigitConstraint {
+    push_index: usize,
+    pop_index: usize,
+    offset: i64,
+}
+
+/// Analyses the MONAD program to determine the constraints that the digits of a valid model number
+/// must satisfy. Each block either pushes its digit (plus B) onto the base-26 stack held in z, or
+/// pops the top value and only avoids pushing again if the digit equals the popped value plus A.
+/// For z to end at 0, every block that can pop must do so without pushing. Returns an error if the
+/// program does not consist of the expected digit-processing blocks.
+fn analyse_monad(program: &[Instruction]) -> Result<Vec<DigitConstraint>, String> {
+    if program.len() != MODEL_NUMBER_DIGITS * MONAD_BLOCK_TEMPLATE.len() {
+        return Err(format!(
+            "Day 24 - expected {} instructions in MONAD program but found {}",
+            MODEL_NUMBER_DIGITS * MONAD_BLOCK_TEMPLATE.len(),
+            program.len()
+        ));
+    }
+    let mut constraints: Vec<DigitConstraint> = vec![];
+    let mut stack: Vec<(usize, i64)> = vec![];
+    for (index, block) in program.chunks(MONAD_BLOCK_TEMPLATE.len()).enumerate() {
+        let (divisor, add_x, add_y) = match_monad_block(block)
+            .ok_or_else(|| format!("Day 24 - block {} does not match MONAD template", index))?;
+        match divisor {
+            1 => stack.push((index, add_y)),
+            26 => {
+                let (push_index, push_add_y) = stack
+                    .pop()
+                    .ok_or_else(|| format!("Day 24 - block {} pops from empty stack", index))?;
+                constraints.push(DigitConstraint {
+                    push_index: push_index,
+                    pop_index: index,
+                    offset: push_add_y + add_x,
+                });
+            }
+            _ => {
+                return Err(format!(
+                    "Day 24 - block {} has bad divisor {}",
+                    index, divisor
+                ))
+            }
+        }
+    }
+    if !stack.is_empty() {
+        return Err(String::from(
+            "Day 24 - MONAD blocks do not pop every pushed digit",
+        ));
+    }
+    return Ok(constraints);
+}
+
+/// Checks if the block of instructions matches the MONAD block template. Returns the values of D,
+/// A and B (in that order) if the block matches.
+fn match_monad_block(block: &[Instruction]) -> Option<(i64, i64, i64)> {
+    let mut values: Vec<i64> = vec![];
+    for (instruction, template_line) in block.iter().zip(MONAD_BLOCK_TEMPLATE.iter()) {
+        let template_instruction = match template_line.split(' ').next_back() {
+            Some("D") | Some("A") | Some("B") => {
+                let literal = match instruction {
+                    Instruction::Add(_, Operand::Literal(value))
+                    | Instruction::Div(_, Operand::Literal(value)) => *value,
+                    _ => return None,
+                };
+                values.push(literal);
+                let prefix = &template_line[..template_line.len() - 1];
+                parse_instruction(&format!("{}{}", prefix, literal)).ok()?
+            }
+            _ => parse_instruction(template_line).ok()?,
+        };
+        if *instruction != template_instruction {
+            return None;
+        }
+    }
+    return Some((values[0], values[1], values[2]));
+}
+
+/// Finds the largest or smallest model number that satisfies all of the digit constraints, with
+/// each digit between 1 and 9. Returns None if no such model number exists.
+fn find_model_number(constraints: &[DigitConstraint], largest: bool) -> Option<Vec<i64>> {
+    let mut digits: Vec<i64> = vec![0; MODEL_NUMBER_DIGITS];
+    for constraint in constraints {
+        let push_digit = {
+            if largest {
+                9.min(9 - constraint.offset)
+            } else {
+                1.max(1 - constraint.offset)
+            }
+        };
+        let pop_digit = push_digit + constraint.offset;
+        if !(1..=9).contains(&push_digit) || !(1..=9).contains(&pop_digit) {
+            return None;
+        }
+        digits[constraint.push_index] = push_digit;
+        digits[constraint.pop_index] = pop_digit;
+    }
+    return Some(digits);
+}
+
+/// Checks if the model number is valid by running it through the MONAD program on the ALU.
+fn validate_model_number(program: &[Instruction], digits: &[i64]) -> bool {
+    let mut alu = Alu::new();
+    if alu.run(program, digits.iter().copied()).is_err() {
+        return false;
+    }
+    return alu.get(Register::Z) == 0;
+}
+
+/// Parses a single line of an ALU program into an instruction.
+fn parse_instruction(line: &str) -> Result<Instruction, String> {
+    let split = line.split_whitespace().collect::<Vec<&str>>();
+    let register = |i: usize| {
+        split
+            .get(i)
+            .and_then(|s| Register::from_name(s))
+            .ok_or_else(|| format!("Day 24 - bad register in instruction: {}", line))
+    };
+    let operand = |i: usize| {
+        let s = split
+            .get(i)
+            .ok_or_else(|| format!("Day 24 - missing operand in instruction: {}", line))?;
+        if let Some(register) = Register::from_name(s) {
+            return Ok(Operand::Register(register));
+        }
+        s.parse::<i64>()
+            .map(Operand::Literal)
+            .map_err(|_| format!("Day 24 - bad operand in instruction: {}", line))
+    };
+    let instruction = match split.first() {
+        Some(&"inp") => Instruction::Inp(register(1)?),
+        Some(&"add") => Instruction::Add(register(1)?, operand(2)?),
+        Some(&"mul") => Instruction::Mul(register(1)?, operand(2)?),
+        Some(&"div") => Instruction::Div(register(1)?, operand(2)?),
+        Some(&"mod") => Instruction::Mod(register(1)?, operand(2)?),
+        Some(&"eql") => Instruction::Eql(register(1)?, operand(2)?),
+        _ => return Err(format!("Day 24 - bad instruction: {}", line)),
+    };
+    return Ok(instruction);
+}
+
+#[aoc_generator(day24)]
+fn parse_input(input: &str) -> Result<Vec<Instruction>, parse::ParseError> {
+    let mut output: Vec<Instruction> = vec![];
+    for (line_num, line) in parse::lines(input) {
+        let instruction =
+            parse_instruction(line).map_err(|e| parse::ParseError::new(line_num, &e))?;
+        output.push(instruction);
+    }
+    return Ok(output);
+}
+
+#[aoc(day24, part1)]
+fn solve_part_1(program: &[Instruction]) -> u64 {
+    return find_valid_model_number(program, true);
+}
+
+#[aoc(day24, part2)]
+fn solve_part_2(program: &[Instruction]) -> u64 {
+    return find_valid_model_number(program, false);
+}
+
+/// Finds the largest or smallest valid model number accepted by the MONAD program, checking the
+/// result derived from the program analysis by running it on the ALU.
+fn find_valid_model_number(program: &[Instruction], largest: bool) -> u64 {
+    let constraints =
+        analyse_monad(program).unwrap_or_else(|e| panic!("Day 24 - bad MONAD program: {}", e));
+    let digits = find_model_number(&constraints, largest)
+        .unwrap_or_else(|| panic!("Day 24 - no model number satisfies the digit constraints"));
+    if !validate_model_number(program, &digits) {
+        panic!(
+            "Day 24 - derived model number rejected by MONAD: {:?}",
+            digits
+        );
+    }
+    return digits.iter().fold(0, |acc, d| acc * 10 + *d as u64);
+}
+
+day_solution!(Day24, 24, Vec<Instruction>);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::*;
+
+    #[test]
+    fn test_d24_alu_binary_conversion() {
+        let program = parse_input(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\n\
+            div w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2\n",
+        )
+        .unwrap();
+        let mut alu = Alu::new();
+        alu.run(&program, vec![13].into_iter()).unwrap();
+        assert_eq!([1, 1, 0, 1], alu.registers);
+    }
+
+    #[test]
+    fn test_d24_alu_errors() {
+        let mut alu = Alu::new();
+        assert!(alu
+            .run(&parse_input("div x 0").unwrap(), vec![].into_iter())
+            .is_err());
+        assert!(alu
+            .run(&parse_input("inp x").unwrap(), vec![].into_iter())
+            .is_err());
+        assert!(alu
+            .run(
+                &parse_input("add x 9223372036854775807\nadd x 1").unwrap(),
+                vec![].into_iter()
+            )
+            .is_err());
+        assert!(alu
+            .run(
+                &parse_input("add y 3037000500\nmul y y").unwrap(),
+                vec![].into_iter()
+            )
+            .is_err());
+        assert!(parse_instruction("sub x 1").is_err());
+        assert!(analyse_monad(&parse_input("inp w\nmul x -1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_d24_monad_constraints_test_001() {
+        // Fixture is a MONAD program with hand-picked block parameters, not a real puzzle input
+        let input = read_to_string("./input/2021/test/day_24_test_001.txt").unwrap();
+        let program = parse_input(&input).unwrap();
+        let constraints = analyse_monad(&program).unwrap();
+        let pairs = constraints
+            .iter()
+            .map(|c| (c.push_index, c.pop_index, c.offset))
+            .collect::<Vec<(usize, usize, i64)>>();
+        let expected = vec![
+            (1, 2, 0),
+            (3, 4, -3),
+            (6, 7, 4),
+            (5, 8, 2),
+            (9, 10, 0),
+            (11, 12, -6),
+            (0, 13, 1),
+        ];
+        assert_eq!(expected, pairs);
+        assert_eq!(89996759999939, solve_part_1(&program));
+        assert_eq!(11141115311712, solve_part_2(&program));
+    }
+}
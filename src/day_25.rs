@@ -0,0 +1,145 @@
+use super::simulation::Simulation;
+use super::utils::map::*;
+use super::utils::parse;
+use super::utils::render::*;
+
+/// Contents of a single location on the sea floor.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SeaFloor {
+    Empty,
+    EastHerd,
+    SouthHerd,
+}
+
+/// Sea floor on which both herds of sea cucumbers move once on each step of the simulation.
+pub struct SeaCucumberSimulation {
+    sea_floor: Vec<Vec<SeaFloor>>,
+    last_moves: Option<usize>,
+}
+
+impl SeaCucumberSimulation {
+    /// Creates a new simulation from the sea floor given in the puzzle input.
+    pub fn from_input(input: &str) -> Result<Self, parse::ParseError> {
+        return Ok(Self {
+            sea_floor: parse_input(input)?,
+            last_moves: None,
+        });
+    }
+
+    /// Checks if no sea cucumbers moved on the last step.
+    pub fn is_stopped(&self) -> bool {
+        return self.last_moves == Some(0);
+    }
+}
+
+impl Simulation for SeaCucumberSimulation {
+    fn step(&mut self) {
+        let east_moves = move_herd(&mut self.sea_floor, SeaFloor::EastHerd, (1, 0));
+        let south_moves = move_herd(&mut self.sea_floor, SeaFloor::SouthHerd, (0, 1));
+        self.last_moves = Some(east_moves + south_moves);
+    }
+
+    /// Draws the sea floor, with the east-facing herd in orange and the south-facing herd in blue.
+    fn snapshot(&self) -> GridImage {
+        return GridImage::from_grid(&self.sea_floor, |location| match location {
+            SeaFloor::Empty => Colour::BLACK,
+            SeaFloor::EastHerd => Colour::new(255, 128, 0),
+            SeaFloor::SouthHerd => Colour::new(0, 128, 255),
+        })
+        .unwrap();
+    }
+}
+
+#[aoc_generator(day25)]
+fn parse_input(input: &str) -> Result<Vec<Vec<SeaFloor>>, parse::ParseError> {
+    let lines = parse::lines(input).collect::<Vec<(usize, &str)>>();
+    return parse::char_grid(&lines, |c| match c {
+        '.' => Some(SeaFloor::Empty),
+        '>' => Some(SeaFloor::EastHerd),
+        'v' => Some(SeaFloor::SouthHerd),
+        _ => None,
+    });
+}
+
+#[aoc(day25, part1)]
+fn solve_part_1(sea_floor: &Vec<Vec<SeaFloor>>) -> u64 {
+    let mut sea_floor = sea_floor.clone();
+    let mut step = 0;
+    loop {
+        step += 1;
+        let east_moves = move_herd(&mut sea_floor, SeaFloor::EastHerd, (1, 0));
+        let south_moves = move_herd(&mut sea_floor, SeaFloor::SouthHerd, (0, 1));
+        if east_moves + south_moves == 0 {
+            return step;
+        }
+    }
+}
+
+/// Moves every sea cucumber in the given herd one location in the given direction, if the location
+/// is empty at the start of the move. Sea cucumbers moving off an edge of the sea floor wrap around
+/// to the opposite edge. Returns the number of sea cucumbers that moved.
+fn move_herd(sea_floor: &mut Vec<Vec<SeaFloor>>, herd: SeaFloor, delta: (i64, i64)) -> usize {
+    let x_max = sea_floor[0].len() - 1;
+    let y_max = sea_floor.len() - 1;
+    // Determine all moves before making any, since the herd moves simultaneously
+    let mut moves: Vec<((usize, usize), (usize, usize))> = vec![];
+    for y in 0..=y_max {
+        for x in 0..=x_max {
+            if sea_floor[y][x] != herd {
+                continue;
+            }
+            let (new_x, new_y) = get_adjacent_point(
+                x,
+                y,
+                delta.0,
+                delta.1,
+                x_max,
+                y_max,
+                NeighbourMode::Toroidal,
+            )
+            .unwrap();
+            if sea_floor[new_y][new_x] == SeaFloor::Empty {
+                moves.push(((x, y), (new_x, new_y)));
+            }
+        }
+    }
+    for ((x, y), (new_x, new_y)) in moves.iter() {
+        sea_floor[*y][*x] = SeaFloor::Empty;
+        sea_floor[*new_y][*new_x] = herd;
+    }
+    return moves.len();
+}
+
+day_solution!(Day25, 25, Vec<Vec<SeaFloor>>, part1);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::*;
+
+    #[test]
+    fn test_d25_herd_wraps_around() {
+        let mut sea_floor = parse_input("...>>>>>...\n").unwrap();
+        move_herd(&mut sea_floor, SeaFloor::EastHerd, (1, 0));
+        move_herd(&mut sea_floor, SeaFloor::EastHerd, (1, 0));
+        assert_eq!(parse_input("...>>>.>.>.\n").unwrap(), sea_floor);
+        let mut sea_floor = parse_input("..>\n").unwrap();
+        move_herd(&mut sea_floor, SeaFloor::EastHerd, (1, 0));
+        assert_eq!(parse_input(">..\n").unwrap(), sea_floor);
+    }
+
+    #[test]
+    fn test_d25_simulation_stops() {
+        let input = read_to_string("./input/2021/test/day_25_test_001.txt").unwrap();
+        let mut simulation = SeaCucumberSimulation::from_input(&input).unwrap();
+        let mut steps = 0;
+        while !simulation.is_stopped() {
+            simulation.step();
+            steps += 1;
+        }
+        assert_eq!(58, steps);
+        let first_row = input.lines().next().unwrap();
+        let image = simulation.snapshot();
+        assert_eq!(first_row.len(), image.width());
+    }
+}
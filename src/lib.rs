@@ -1,7 +1,14 @@
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod runner;
+pub mod simulation;
+#[macro_use]
+pub mod solution;
+#[macro_use]
 pub mod utils;
+pub mod verify;
+pub mod viewer;
 
 // Modules for day solutions
 pub mod day_01;
@@ -26,5 +33,8 @@ pub mod day_19;
 pub mod day_20;
 pub mod day_21;
 pub mod day_22;
+pub mod day_23;
+pub mod day_24;
+pub mod day_25;
 
 aoc_lib!{ year = 2021 }
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::solution::*;
+
+/// Answer and time taken for a single part of a day's solution.
+pub struct PartReport {
+    pub part: u8,
+    pub answer: String,
+    pub time: Duration,
+}
+
+/// Answers and times taken from running a day's solution on a single input.
+pub struct DayReport {
+    pub day: u8,
+    pub parse_time: Duration,
+    pub parts: Vec<PartReport>,
+}
+
+/// Errors that can occur when running a day's solution.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RunError {
+    UnknownDay(u8),
+    UnknownPart { day: u8, part: u8 },
+    Parse { day: u8, message: String },
+    Solve { day: u8, part: u8, message: String },
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::UnknownDay(day) => write!(f, "no solution for day {}", day),
+            RunError::UnknownPart { day, part } => {
+                write!(f, "no solution for day {} part {}", day, part)
+            }
+            RunError::Parse { day, message } => {
+                write!(f, "day {} - failed to parse input: {}", day, message)
+            }
+            RunError::Solve { day, part, message } => {
+                write!(
+                    f,
+                    "day {} part {} - failed to solve: {}",
+                    day, part, message
+                )
+            }
+        }
+    }
+}
+
+/// Runs the solution for the given day on the input. Parts are numbered from 1, and all parts with
+/// a solution are run if no parts are given.
+pub fn run_day(day: u8, input: &str, parts: &[u8]) -> Result<DayReport, RunError> {
+    match registry().iter().find(|solution| solution.day() == day) {
+        Some(solution) => solution.run(input, parts),
+        None => Err(RunError::UnknownDay(day)),
+    }
+}
+
+/// Gets the path of the puzzle input file for the given day, as used by the tests.
+pub fn default_input_path(day: u8) -> String {
+    return format!("./input/2021/day{}.txt", day);
+}
+
+/// Formats the reports as a table of the time taken by the generator and each part for each day.
+pub fn format_summary_table(reports: &[DayReport]) -> String {
+    let mut output =
+        String::from("| Day | Generator (ms) | Part 1 (ms) | Part 2 (ms) | Total (ms) |\n");
+    output.push_str("|-----|----------------|-------------|-------------|------------|\n");
+    for report in reports {
+        let part_time = |part: u8| {
+            report
+                .parts
+                .iter()
+                .find(|p| p.part == part)
+                .map(|p| format_millis(p.time))
+                .unwrap_or_else(|| String::from("-"))
+        };
+        let total = report.parse_time + report.parts.iter().map(|p| p.time).sum::<Duration>();
+        output.push_str(&format!(
+            "| {:<3} | {:>14} | {:>11} | {:>11} | {:>10} |\n",
+            report.day,
+            format_millis(report.parse_time),
+            part_time(1),
+            part_time(2),
+            format_millis(total)
+        ));
+    }
+    return output;
+}
+
+/// Formats the duration in milliseconds.
+pub fn format_millis(time: Duration) -> String {
+    return format!("{:.3}", time.as_secs_f64() * 1000.0);
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use super::utils::parse::ParseError;
+use super::utils::render::*;
+
+/// State that evolves over a series of steps, such as the octopus energy levels of Day 11.
+pub trait Simulation {
+    /// Advances the simulation by a single step.
+    fn step(&mut self);
+
+    /// Draws the current state of the simulation as an image.
+    fn snapshot(&self) -> GridImage;
+}
+
+/// Creates the simulation for the given day from the puzzle input. Returns an error if the day has
+/// no simulation or the input is malformed.
+pub fn simulation_for_day(day: u8, input: &str) -> Result<Box<dyn Simulation>, String> {
+    let simulation: Result<Box<dyn Simulation>, ParseError> = match day {
+        6 => crate::day_06::LanternfishSimulation::from_input(input).map(|s| Box::new(s) as _),
+        11 => crate::day_11::OctopusSimulation::from_input(input).map(|s| Box::new(s) as _),
+        20 => crate::day_20::ImageEnhancement::from_input(input).map(|s| Box::new(s) as _),
+        25 => crate::day_25::SeaCucumberSimulation::from_input(input).map(|s| Box::new(s) as _),
+        _ => return Err(format!("no simulation for day {}", day)),
+    };
+    return simulation.map_err(|e| format!("day {} - failed to parse input: {}", day, e));
+}
+
+/// Captures a snapshot of a simulation after each step, so that the steps can be written out as a
+/// sequence of frames.
+pub struct Recorder {
+    frames: Vec<GridImage>,
+}
+
+impl Recorder {
+    /// Creates a new recorder with no frames.
+    pub fn new() -> Self {
+        Self { frames: vec![] }
+    }
+
+    /// Runs the simulation for the given number of steps, capturing its initial state and its
+    /// state after each step.
+    pub fn record<S: Simulation + ?Sized>(&mut self, simulation: &mut S, steps: usize) {
+        self.frames.push(simulation.snapshot());
+        for _ in 0..steps {
+            simulation.step();
+            self.frames.push(simulation.snapshot());
+        }
+    }
+
+    /// Gets the frames captured so far.
+    pub fn frames(&self) -> &[GridImage] {
+        return &self.frames;
+    }
+
+    /// Writes each frame to the directory as a PPM image named `frame_NNNN.ppm`, numbered from 0.
+    /// The directory is created if it does not exist. Returns the paths of the files written.
+    pub fn write_ppm_frames(&self, dir: &str, scale: usize) -> io::Result<Vec<String>> {
+        fs::create_dir_all(dir)?;
+        let mut paths: Vec<String> = vec![];
+        for (i, frame) in self.frames.iter().enumerate() {
+            let path = Path::new(dir).join(format!("frame_{:04}.ppm", i));
+            let path = path.to_string_lossy().to_string();
+            frame.write_ppm(&path, scale)?;
+            paths.push(path);
+        }
+        return Ok(paths);
+    }
+
+    /// Writes the frames to the given file as an animated GIF that loops forever, showing each
+    /// frame for the given delay in hundredths of a second. Frames smaller than the largest frame
+    /// are centred, with the space around them filled with the colour of their top-left cell.
+    pub fn write_gif(&self, path: &str, scale: usize, delay: u16) -> io::Result<()> {
+        let scale = scale.max(1);
+        let width = self.frames.iter().map(|f| f.width()).max().unwrap_or(0);
+        let height = self.frames.iter().map(|f| f.height()).max().unwrap_or(0);
+        let (gif_width, gif_height) =
+            match (u16::try_from(width * scale), u16::try_from(height * scale)) {
+                (Ok(w), Ok(h)) if w > 0 && h > 0 => (w, h),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("cannot write {}x{} GIF", width * scale, height * scale),
+                    ))
+                }
+            };
+        let mut encoder = gif::Encoder::new(File::create(path)?, gif_width, gif_height, &[])
+            .map_err(to_io_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(to_io_error)?;
+        for frame in self.frames.iter() {
+            let mut gif_frame = encode_frame(&centre_frame(frame, width, height), scale);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame).map_err(to_io_error)?;
+        }
+        return Ok(());
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Pads the frame out to the given size, keeping it centred.
+fn centre_frame(frame: &GridImage, width: usize, height: usize) -> GridImage {
+    if frame.width() == width && frame.height() == height {
+        return frame.clone();
+    }
+    let fill = frame.get(0, 0).unwrap_or(Colour::BLACK);
+    let x_offset = (width - frame.width()) / 2;
+    let y_offset = (height - frame.height()) / 2;
+    let grid = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    match (x.checked_sub(x_offset), y.checked_sub(y_offset)) {
+                        (Some(x), Some(y)) => frame.get(x, y),
+                        _ => None,
+                    }
+                    .unwrap_or(fill)
+                })
+                .collect::<Vec<Colour>>()
+        })
+        .collect::<Vec<Vec<Colour>>>();
+    return GridImage::from_grid(&grid, |c| *c).unwrap();
+}
+
+/// Converts the frame into a GIF frame, scaling each cell up to a square of `scale` pixels. Frames
+/// with at most 256 colours are encoded exactly, and others are quantised.
+fn encode_frame(frame: &GridImage, scale: usize) -> gif::Frame<'static> {
+    let width = frame.width() * scale;
+    let height = frame.height() * scale;
+    let colours = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| frame.get(x / scale, y / scale).unwrap())
+        .collect::<Vec<Colour>>();
+    let mut palette: HashMap<Colour, u8> = HashMap::new();
+    for colour in colours.iter() {
+        let next_index = palette.len();
+        if !palette.contains_key(colour) {
+            if next_index == 256 {
+                let rgb = colours
+                    .iter()
+                    .flat_map(|c| [c.r, c.g, c.b])
+                    .collect::<Vec<u8>>();
+                return gif::Frame::from_rgb_speed(width as u16, height as u16, &rgb, 10);
+            }
+            palette.insert(*colour, next_index as u8);
+        }
+    }
+    let mut palette_rgb = vec![0; palette.len() * 3];
+    for (colour, index) in palette.iter() {
+        let i = *index as usize * 3;
+        palette_rgb[i..i + 3].copy_from_slice(&[colour.r, colour.g, colour.b]);
+    }
+    let pixels = colours.iter().map(|c| palette[c]).collect::<Vec<u8>>();
+    return gif::Frame::from_palette_pixels(width as u16, height as u16, pixels, palette_rgb, None);
+}
+
+/// Converts an error from the GIF encoder into an IO error.
+fn to_io_error(error: gif::EncodingError) -> io::Error {
+    match error {
+        gif::EncodingError::Io(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::fs::*;
+
+    /// Simulation whose single cell counts up the steps taken.
+    struct Counter {
+        count: u64,
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) {
+            self.count += 1;
+        }
+
+        fn snapshot(&self) -> GridImage {
+            return GridImage::from_values(&[vec![self.count]], &Palette::greyscale(4)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_recorder_frames() {
+        let mut recorder = Recorder::new();
+        recorder.record(&mut Counter { count: 0 }, 3);
+        let frames = recorder.frames();
+        assert_eq!(4, frames.len());
+        assert_eq!(Some(Colour::BLACK), frames[0].get(0, 0));
+        assert_eq!(Some(Colour::WHITE), frames[3].get(0, 0));
+        let dir = env::temp_dir().join(format!("aoc2021_frames_{}", std::process::id()));
+        let paths = recorder
+            .write_ppm_frames(&dir.to_string_lossy(), 2)
+            .unwrap();
+        assert!(paths[3].ends_with("frame_0003.ppm"));
+        assert_eq!(b"P6\n2 2\n255\n", &read(&paths[0]).unwrap()[..11]);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recorder_gif() {
+        let input = read_to_string("./input/2021/test/day_20_test_001.txt").unwrap();
+        let mut recorder = Recorder::new();
+        recorder.record(&mut *simulation_for_day(20, &input).unwrap(), 2);
+        // Enhanced image grows by one cell on each side every step
+        let frames = recorder.frames();
+        assert_eq!(frames[0].width() + 4, frames[2].width());
+        let path = env::temp_dir().join(format!("aoc2021_{}.gif", std::process::id()));
+        recorder.write_gif(&path.to_string_lossy(), 3, 50).unwrap();
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!(frames[2].width() as u16 * 3, decoder.width());
+        let mut frame_count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(50, frame.delay);
+            frame_count += 1;
+        }
+        assert_eq!(3, frame_count);
+        remove_file(&path).unwrap();
+        assert!(simulation_for_day(12, &input).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::cell::Cell;
+use std::fmt::Display;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::panic::UnwindSafe;
+use std::sync::Once;
+use std::time::Instant;
+
+use super::runner::*;
+
+/// Implements the Solution trait for a day module as the given unit struct, using the module's
+/// `parse_input` generator (which returns a `Result` with a `ParseError`) and `solve_part_1` (and
+/// optionally `solve_part_2`) solvers.
+macro_rules! day_solution {
+    ($name:ident, $day:expr, $input:ty) => {
+        day_solution!(@impl $name, $day, $input, |input| Some(solve_part_2(input)));
+    };
+    ($name:ident, $day:expr, $input:ty, part1) => {
+        day_solution!(@impl $name, $day, $input, |_| None::<String>);
+    };
+    (@impl $name:ident, $day:expr, $input:ty, $part2:expr) => {
+        #[doc = concat!("Solution for AOC 2021 Day ", stringify!($day), ".")]
+        pub struct $name;
+
+        impl crate::solution::Solution for $name {
+            type Input = $input;
+
+            const DAY: u8 = $day;
+
+            fn parse(&self, input: &str) -> Result<Self::Input, String> {
+                crate::solution::catch_panic(|| parse_input(input))?.map_err(|e| e.to_string())
+            }
+
+            fn part1(&self, input: &Self::Input) -> impl std::fmt::Display {
+                solve_part_1(input)
+            }
+
+            fn part2(&self, input: &Self::Input) -> Option<impl std::fmt::Display> {
+                let part2 = $part2;
+                part2(input)
+            }
+        }
+    };
+}
+
+/// Common interface to the solution for a single day, for calling the solvers as a library.
+pub trait Solution {
+    /// Value generated from the puzzle input, which is used by both parts.
+    type Input;
+
+    /// Day of the puzzle solved.
+    const DAY: u8;
+
+    /// Generates the input value for the solvers from the puzzle input. Returns an error if the
+    /// puzzle input is malformed.
+    fn parse(&self, input: &str) -> Result<Self::Input, String>;
+
+    /// Solves Part 1 of the puzzle.
+    fn part1(&self, input: &Self::Input) -> impl Display;
+
+    /// Solves Part 2 of the puzzle. Returns None if there is no solution for Part 2.
+    fn part2(&self, input: &Self::Input) -> Option<impl Display>;
+}
+
+/// Type-erased form of a Solution, allowing all days to be held in the registry together.
+pub trait DaySolution {
+    /// Gets the day of the puzzle solved.
+    fn day(&self) -> u8;
+
+    /// Runs the solution on the puzzle input. Parts are numbered from 1, and all parts with a
+    /// solution are run if no parts are given.
+    fn run(&self, input: &str, parts: &[u8]) -> Result<DayReport, RunError>;
+}
+
+impl<S: Solution> DaySolution for S {
+    fn day(&self) -> u8 {
+        return S::DAY;
+    }
+
+    fn run(&self, input: &str, parts: &[u8]) -> Result<DayReport, RunError> {
+        let day = S::DAY;
+        if let Some(part) = parts.iter().find(|part| **part != 1 && **part != 2) {
+            return Err(RunError::UnknownPart {
+                day: day,
+                part: *part,
+            });
+        }
+        let start = Instant::now();
+        let generated = self.parse(input).map_err(|message| RunError::Parse {
+            day: day,
+            message: message,
+        })?;
+        let parse_time = start.elapsed();
+        let mut part_reports: Vec<PartReport> = vec![];
+        let run_parts = {
+            if parts.is_empty() {
+                vec![1, 2]
+            } else {
+                parts.to_vec()
+            }
+        };
+        for part in run_parts {
+            let start = Instant::now();
+            let answer = catch_panic(AssertUnwindSafe(|| match part {
+                1 => Some(self.part1(&generated).to_string()),
+                _ => self.part2(&generated).map(|answer| answer.to_string()),
+            }))
+            .map_err(|message| RunError::Solve {
+                day: day,
+                part: part,
+                message: message,
+            })?;
+            let time = start.elapsed();
+            match answer {
+                Some(answer) => part_reports.push(PartReport {
+                    part: part,
+                    answer: answer,
+                    time: time,
+                }),
+                None if !parts.is_empty() => {
+                    return Err(RunError::UnknownPart {
+                        day: day,
+                        part: part,
+                    })
+                }
+                None => (),
+            }
+        }
+        return Ok(DayReport {
+            day: day,
+            parse_time: parse_time,
+            parts: part_reports,
+        });
+    }
+}
+
+/// Gets the solutions for all days, ordered by day.
+pub fn registry() -> Vec<Box<dyn DaySolution>> {
+    return vec![
+        Box::new(crate::day_01::Day01),
+        Box::new(crate::day_02::Day02),
+        Box::new(crate::day_03::Day03),
+        Box::new(crate::day_04::Day04),
+        Box::new(crate::day_05::Day05),
+        Box::new(crate::day_06::Day06),
+        Box::new(crate::day_07::Day07),
+        Box::new(crate::day_08::Day08),
+        Box::new(crate::day_09::Day09),
+        Box::new(crate::day_10::Day10),
+        Box::new(crate::day_11::Day11),
+        Box::new(crate::day_12::Day12),
+        Box::new(crate::day_13::Day13),
+        Box::new(crate::day_14::Day14),
+        Box::new(crate::day_15::Day15),
+        Box::new(crate::day_16::Day16),
+        Box::new(crate::day_17::Day17),
+        Box::new(crate::day_18::Day18),
+        Box::new(crate::day_19::Day19),
+        Box::new(crate::day_20::Day20),
+        Box::new(crate::day_21::Day21),
+        Box::new(crate::day_22::Day22),
+        Box::new(crate::day_23::Day23),
+        Box::new(crate::day_24::Day24),
+        Box::new(crate::day_25::Day25),
+    ];
+}
+
+thread_local! {
+    /// Whether panics on this thread are currently being caught by `catch_panic`.
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Calls the function, converting any panic raised (e.g. from malformed input) into an error. The
+/// panic message is reported through the error, so it is not printed by the panic hook. Panics
+/// outside of this function (or on other threads) still reach the previously installed hook.
+pub(crate) fn catch_panic<T, F: FnOnce() -> T + UnwindSafe>(f: F) -> Result<T, String> {
+    static INSTALL_HOOK: Once = Once::new();
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING_PANIC.with(|catching| catching.get()) {
+                previous_hook(info);
+            }
+        }));
+    });
+    let was_catching = CATCHING_PANIC.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(f);
+    CATCHING_PANIC.with(|catching| catching.set(was_catching));
+    return result.map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("unknown error")
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::*;
+
+    #[test]
+    fn test_registry_has_all_days() {
+        let days = registry()
+            .iter()
+            .map(|solution| solution.day())
+            .collect::<Vec<u8>>();
+        assert_eq!((1..=25).collect::<Vec<u8>>(), days);
+    }
+
+    #[test]
+    fn test_solution_as_library() {
+        let solution = crate::day_13::Day13;
+        let input = solution
+            .parse(&read_to_string("./input/2021/test/day_13_test_001.txt").unwrap())
+            .unwrap();
+        assert_eq!("17", solution.part1(&input).to_string());
+        assert!(solution.parse("not,a,dot\n").is_err());
+    }
+
+    #[test]
+    fn test_registry_run_missing_part() {
+        let input = read_to_string("./input/2021/test/day_25_test_001.txt").unwrap();
+        let report = run_day(25, &input, &[]).unwrap();
+        assert_eq!(1, report.parts.len());
+        assert_eq!("58", report.parts[0].answer);
+        let expected = RunError::UnknownPart { day: 25, part: 2 };
+        assert_eq!(Err(expected), run_day(25, &input, &[2]).map(|r| r.day));
+    }
+}
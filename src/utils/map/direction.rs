@@ -0,0 +1,66 @@
+/// Compass direction on a two-dimensional map, where north is towards decreasing y-coordinates
+/// and east is towards increasing x-coordinates.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All eight directions, in clockwise order starting from north.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The four cardinal directions, in clockwise order starting from north.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Gets the change in x- and y-coordinates from taking a single step in the direction.
+    pub fn delta(&self) -> (i64, i64) {
+        return match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        };
+    }
+
+    /// Gets the direction resulting from turning 90 degrees anticlockwise.
+    pub fn turn_left(&self) -> Direction {
+        return self.rotate(-2);
+    }
+
+    /// Gets the direction resulting from turning 90 degrees clockwise.
+    pub fn turn_right(&self) -> Direction {
+        return self.rotate(2);
+    }
+
+    /// Gets the direction resulting from turning the given number of 45-degree steps clockwise
+    /// (or anticlockwise, if negative).
+    pub fn rotate(&self, steps: i64) -> Direction {
+        let index = Direction::ALL.iter().position(|d| d == self).unwrap() as i64;
+        return Direction::ALL[(index + steps).rem_euclid(8) as usize];
+    }
+}
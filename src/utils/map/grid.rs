@@ -113,3 +113,40 @@ pub fn get_surrounding_points(x: usize, y: usize, x_max: usize, y_max: usize) ->
         ]
     }
 }
+
+/// Determines how the edges of a 2D grid are treated when finding neighbouring points.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NeighbourMode {
+    /// Points beyond the edges of the grid do not exist.
+    Bounded,
+    /// Points beyond an edge of the grid wrap around to the opposite edge.
+    Toroidal,
+}
+
+/// Calculates the point offset from (x, y) by the given deltas in a 2D grid with non-negative
+/// indices bounded by given maximum x- and y-values. Returns None if the point would be outside of
+/// the grid in bounded mode.
+pub fn get_adjacent_point(
+    x: usize,
+    y: usize,
+    delta_x: i64,
+    delta_y: i64,
+    x_max: usize,
+    y_max: usize,
+    mode: NeighbourMode,
+) -> Option<(usize, usize)> {
+    let new_x = x as i64 + delta_x;
+    let new_y = y as i64 + delta_y;
+    match mode {
+        NeighbourMode::Bounded => {
+            if new_x < 0 || new_y < 0 || new_x > x_max as i64 || new_y > y_max as i64 {
+                return None;
+            }
+            Some((new_x as usize, new_y as usize))
+        }
+        NeighbourMode::Toroidal => Some((
+            new_x.rem_euclid(x_max as i64 + 1) as usize,
+            new_y.rem_euclid(y_max as i64 + 1) as usize,
+        )),
+    }
+}
@@ -1,5 +1,7 @@
 mod point2d;
 mod grid;
+mod direction;
 
 pub use self::point2d::Point2D;
 pub use self::grid::*;
+pub use self::direction::Direction;
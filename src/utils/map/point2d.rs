@@ -1,4 +1,11 @@
-/// Represents a single point in two-dimensional Euclidean space.
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use super::Direction;
+
+/// Represents a single point in two-dimensional Euclidean space. Points are ordered row-major, i.e.
+/// by y-coordinate and then by x-coordinate.
 #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
 pub struct Point2D {
     x: i64,
@@ -55,8 +62,156 @@ impl Point2D {
         return output;
     }
 
+    /// Gets the eight surrounding points from the current location, in the same order as
+    /// get_surrounding_points. Returns None if integer overflow or underflow would occur.
+    pub fn checked_get_surrounding_points(&self) -> Option<Vec<Point2D>> {
+        let mut output: Vec<Point2D> = vec![];
+        for dir in Direction::ALL {
+            let (delta_x, delta_y) = dir.delta();
+            output.push(Point2D::new(
+                self.x.checked_add(delta_x)?,
+                self.y.checked_add(delta_y)?,
+            ));
+        }
+        return Some(output);
+    }
+
+    /// Gets the point reached by taking a single step from the current point in the direction.
+    pub fn step(&self, dir: Direction) -> Point2D {
+        let (delta_x, delta_y) = dir.delta();
+        return Point2D::new(self.x + delta_x, self.y + delta_y);
+    }
+
     /// Calculates the Manhattan distance between the current point and the other point.
     pub fn calculate_manhattan_distance(&self, other: &Point2D) -> u64 {
         return (self.x - other.x).abs() as u64 + (self.y - other.y).abs() as u64;
     }
+
+    /// Calculates the Chebyshev distance between the current point and the other point, i.e. the
+    /// number of king moves between them.
+    pub fn calculate_chebyshev_distance(&self, other: &Point2D) -> u64 {
+        return (self.x - other.x).unsigned_abs().max((self.y - other.y).unsigned_abs());
+    }
+}
+
+impl Ord for Point2D {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.y.cmp(&other.y).then(self.x.cmp(&other.x));
+    }
+}
+
+impl PartialOrd for Point2D {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Add for Point2D {
+    type Output = Point2D;
+
+    fn add(self, other: Point2D) -> Point2D {
+        return Point2D::new(self.x + other.x, self.y + other.y);
+    }
+}
+
+impl Sub for Point2D {
+    type Output = Point2D;
+
+    fn sub(self, other: Point2D) -> Point2D {
+        return Point2D::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl Neg for Point2D {
+    type Output = Point2D;
+
+    fn neg(self) -> Point2D {
+        return Point2D::new(-self.x, -self.y);
+    }
+}
+
+impl Mul<i64> for Point2D {
+    type Output = Point2D;
+
+    fn mul(self, scalar: i64) -> Point2D {
+        return Point2D::new(self.x * scalar, self.y * scalar);
+    }
+}
+
+impl FromStr for Point2D {
+    type Err = String;
+
+    /// Parses a point from a string of the form "x,y".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("expected \"x,y\", got \"{}\"", s))?;
+        let x = x
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| format!("bad x-coordinate in \"{}\": {}", s, e))?;
+        let y = y
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| format!("bad y-coordinate in \"{}\": {}", s, e))?;
+        return Ok(Point2D::new(x, y));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_point2d_arithmetic() {
+        let a = Point2D::new(3, -2);
+        let b = Point2D::new(-1, 5);
+        assert_eq!(Point2D::new(2, 3), a + b);
+        assert_eq!(Point2D::new(4, -7), a - b);
+        assert_eq!(Point2D::new(-3, 2), -a);
+        assert_eq!(Point2D::new(9, -6), a * 3);
+        assert_eq!(7, a.calculate_chebyshev_distance(&b));
+        assert_eq!(11, a.calculate_manhattan_distance(&b));
+    }
+
+    #[test]
+    fn test_point2d_row_major_order() {
+        let mut points = vec![
+            Point2D::new(1, 1),
+            Point2D::new(0, 2),
+            Point2D::new(2, 0),
+            Point2D::new(0, 1),
+        ];
+        points.sort();
+        let expected = vec![
+            Point2D::new(2, 0),
+            Point2D::new(0, 1),
+            Point2D::new(1, 1),
+            Point2D::new(0, 2),
+        ];
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn test_point2d_from_str() {
+        assert_eq!(Ok(Point2D::new(12, -4)), "12,-4".parse::<Point2D>());
+        assert_eq!(Ok(Point2D::new(1, 2)), " 1, 2 ".parse::<Point2D>());
+        assert!("12".parse::<Point2D>().is_err());
+        assert!("a,2".parse::<Point2D>().is_err());
+    }
+
+    #[test]
+    fn test_point2d_directions() {
+        let point = Point2D::new(0, 0);
+        assert_eq!(Point2D::new(0, -1), point.step(Direction::North));
+        assert_eq!(Point2D::new(-1, 1), point.step(Direction::SouthWest));
+        assert_eq!(Direction::West, Direction::North.turn_left());
+        assert_eq!(Direction::SouthEast, Direction::NorthEast.turn_right());
+        assert_eq!(Direction::NorthWest, Direction::North.rotate(-1));
+        assert_eq!(
+            Some(point.get_surrounding_points()),
+            point.checked_get_surrounding_points()
+        );
+        assert_eq!(None, Point2D::new(i64::MAX, 0).checked_get_surrounding_points());
+    }
 }
@@ -1 +1,4 @@
 pub mod map;
+#[macro_use]
+pub mod parse;
+pub mod render;
@@ -0,0 +1,267 @@
+use std::error;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use regex::{Captures, Regex};
+
+/// Compiles the regex on first use and reuses it on later calls from the same call site. Panics
+/// if the pattern is invalid.
+macro_rules! cached_regex {
+    ($pattern:expr) => {{
+        static REGEX: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        REGEX.get_or_init(|| regex::Regex::new($pattern).unwrap())
+    }};
+}
+
+/// Error from parsing puzzle input, along with the line number (counting from 1) at which it
+/// occurred.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Creates a new parse error for the given line.
+    pub fn new(line: usize, message: &str) -> Self {
+        Self {
+            line: line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Gets the lines of the input with leading and trailing whitespace trimmed, skipping empty lines.
+/// Each line is paired with its line number (counting from 1).
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    return input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+}
+
+/// Reads the lines from the reader one at a time, so the whole input does not need to be held in
+/// memory. Lines are trimmed, skipped if empty and paired with their line numbers as for `lines`.
+/// Iteration stops after the first read error, which is reported against the line being read.
+pub fn read_lines<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<(usize, String), ParseError>> {
+    return reader
+        .lines()
+        .enumerate()
+        .scan(false, |failed, (i, line)| {
+            if *failed {
+                return None;
+            }
+            *failed = line.is_err();
+            Some((i + 1, line))
+        })
+        .filter_map(|(line_num, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(Ok((line_num, line.trim().to_string()))),
+            Err(e) => Some(Err(ParseError::new(
+                line_num,
+                &format!("failed to read line: {}", e),
+            ))),
+        });
+}
+
+/// Gets the first non-empty line of the input along with its line number. Returns an error if the
+/// input has no non-empty lines.
+pub fn first_line(input: &str) -> Result<(usize, &str), ParseError> {
+    return lines(input)
+        .next()
+        .ok_or_else(|| ParseError::new(1, "input is empty"));
+}
+
+/// Splits the input into blocks of lines separated by one or more blank lines. Lines are trimmed
+/// and paired with their line numbers as for `lines`.
+pub fn blocks(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut output: Vec<Vec<(usize, &str)>> = vec![];
+    let mut block: Vec<(usize, &str)> = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !block.is_empty() {
+                output.push(block);
+                block = vec![];
+            }
+            continue;
+        }
+        block.push((i + 1, line));
+    }
+    if !block.is_empty() {
+        output.push(block);
+    }
+    return output;
+}
+
+/// Parses a single value from the given line.
+pub fn value<T: FromStr>(line: usize, s: &str) -> Result<T, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    return s
+        .trim()
+        .parse::<T>()
+        .map_err(|e| ParseError::new(line, &format!("invalid value \"{}\": {}", s.trim(), e)));
+}
+
+/// Parses each non-empty line of the input as a single value.
+pub fn parse_lines<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    return lines(input)
+        .map(|(line, s)| value::<T>(line, s))
+        .collect::<Result<Vec<T>, ParseError>>();
+}
+
+/// Parses the values in the given line separated by the delimiter.
+pub fn separated<T: FromStr>(line: usize, s: &str, delimiter: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    return s
+        .split(delimiter)
+        .map(|x| value::<T>(line, x))
+        .collect::<Result<Vec<T>, ParseError>>();
+}
+
+/// Parses the values in the given line separated by whitespace.
+pub fn whitespace_separated<T: FromStr>(line: usize, s: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    return s
+        .split_whitespace()
+        .map(|x| value::<T>(line, x))
+        .collect::<Result<Vec<T>, ParseError>>();
+}
+
+/// Parses the comma-separated values on the first non-empty line of the input.
+pub fn comma_separated<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    let (line, s) = first_line(input)?;
+    return separated::<T>(line, s, ",");
+}
+
+/// Parses the lines as a grid, converting each character with the given function. Returns an
+/// error if the function rejects a character or the rows are not all the same length.
+pub fn char_grid<T, F: Fn(char) -> Option<T>>(
+    lines: &[(usize, &str)],
+    convert: F,
+) -> Result<Vec<Vec<T>>, ParseError> {
+    let mut output: Vec<Vec<T>> = vec![];
+    for (line, s) in lines {
+        let row = s
+            .chars()
+            .map(|c| {
+                convert(c).ok_or_else(|| {
+                    ParseError::new(*line, &format!("invalid character '{}' in grid", c))
+                })
+            })
+            .collect::<Result<Vec<T>, ParseError>>()?;
+        if !output.is_empty() && row.len() != output[0].len() {
+            return Err(ParseError::new(
+                *line,
+                &format!(
+                    "expected {} cells in row, got {}",
+                    output[0].len(),
+                    row.len()
+                ),
+            ));
+        }
+        output.push(row);
+    }
+    return Ok(output);
+}
+
+/// Parses the non-empty lines of the input as a grid of single decimal digits.
+pub fn digit_grid<T: From<u8>>(input: &str) -> Result<Vec<Vec<T>>, ParseError> {
+    let lines = lines(input).collect::<Vec<(usize, &str)>>();
+    return char_grid(&lines, |c| c.to_digit(10).map(|d| T::from(d as u8)));
+}
+
+/// Matches the whole of the given line against the regex. Returns an error if the line does not
+/// match.
+pub fn captures<'a>(regex: &Regex, line: usize, s: &'a str) -> Result<Captures<'a>, ParseError> {
+    return regex.captures(s).ok_or_else(|| {
+        ParseError::new(
+            line,
+            &format!("\"{}\" does not match the expected format", s),
+        )
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_and_blocks() {
+        let input = "  12\n\n7 \n\n\nx\n";
+        assert_eq!(
+            vec![(1, "12"), (3, "7"), (6, "x")],
+            lines(input).collect::<Vec<(usize, &str)>>()
+        );
+        assert_eq!(
+            vec![vec![(1, "12")], vec![(3, "7")], vec![(6, "x")]],
+            blocks(input)
+        );
+        assert_eq!(
+            Err(ParseError::new(
+                6,
+                "invalid value \"x\": invalid digit found in string"
+            )),
+            parse_lines::<u64>(input)
+        );
+        assert_eq!(Ok(vec![3, -4, 5]), comma_separated::<i64>("\n3,-4, 5\n"));
+        assert!(first_line("\n \n").is_err());
+    }
+
+    #[test]
+    fn test_parse_read_lines() {
+        let input = &b"  12\n\n7 \n\xff\nx\n"[..];
+        let read = read_lines(input).collect::<Vec<Result<(usize, String), ParseError>>>();
+        assert_eq!(Ok((1, String::from("12"))), read[0]);
+        assert_eq!(Ok((3, String::from("7"))), read[1]);
+        // Invalid UTF-8 stops reading at the line where it occurs
+        assert_eq!(4, read[2].clone().unwrap_err().line);
+        assert_eq!(3, read.len());
+    }
+
+    #[test]
+    fn test_parse_digit_grid() {
+        assert_eq!(
+            Ok(vec![vec![1, 2], vec![3, 4]]),
+            digit_grid::<u64>("12\n34\n")
+        );
+        assert_eq!(2, digit_grid::<u8>("12\n3a\n").unwrap_err().line);
+        assert_eq!(3, digit_grid::<u8>("12\n\n345\n").unwrap_err().line);
+    }
+
+    #[test]
+    fn test_parse_captures() {
+        let regex = cached_regex!(r"^(\d+)-(\d+)$");
+        assert_eq!("4", &captures(regex, 1, "4-7").unwrap()[1]);
+        assert_eq!(2, captures(regex, 2, "4 7").unwrap_err().line);
+        // Regex is compiled once per call site
+        let regexes = (0..2)
+            .map(|_| cached_regex!(r"^x$") as *const Regex)
+            .collect::<Vec<*const Regex>>();
+        assert_eq!(regexes[0], regexes[1]);
+    }
+}
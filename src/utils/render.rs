@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+
+/// Colour of a single cell, given by its red, green and blue components.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Colour {
+    pub const BLACK: Colour = Colour::new(0, 0, 0);
+    pub const WHITE: Colour = Colour::new(255, 255, 255);
+    pub const RED: Colour = Colour::new(255, 0, 0);
+    pub const YELLOW: Colour = Colour::new(255, 255, 0);
+
+    /// Creates a new colour from its red, green and blue components.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r: r, g: g, b: b }
+    }
+}
+
+impl fmt::Display for Colour {
+    /// Formats the colour as a hexadecimal colour code, such as "#ff8000".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Ordered list of colours used to draw cells holding small integer values, such as heights or
+/// energy levels. Each value is drawn with the colour at its index in the palette.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Palette {
+    colours: Vec<Colour>,
+}
+
+impl Palette {
+    /// Creates a new palette from the given colours. Returns None if there are no colours.
+    pub fn new(colours: Vec<Colour>) -> Option<Self> {
+        if colours.is_empty() {
+            return None;
+        }
+        return Some(Self { colours: colours });
+    }
+
+    /// Creates a palette that blends evenly from the start colour to the end colour over the given
+    /// number of levels (at least 2).
+    pub fn gradient(start: Colour, end: Colour, levels: usize) -> Self {
+        let levels = levels.max(2);
+        let blend = |a: u8, b: u8, i: usize| {
+            (a as i64 + (b as i64 - a as i64) * i as i64 / (levels as i64 - 1)) as u8
+        };
+        let colours = (0..levels)
+            .map(|i| {
+                Colour::new(
+                    blend(start.r, end.r, i),
+                    blend(start.g, end.g, i),
+                    blend(start.b, end.b, i),
+                )
+            })
+            .collect::<Vec<Colour>>();
+        return Self { colours: colours };
+    }
+
+    /// Creates a palette that blends from black to white over the given number of levels. Ten
+    /// levels suit grids of single decimal digits.
+    pub fn greyscale(levels: usize) -> Self {
+        return Palette::gradient(Colour::BLACK, Colour::WHITE, levels);
+    }
+
+    /// Gets the colour for the given value. Values beyond the end of the palette are drawn with its
+    /// last colour.
+    pub fn colour(&self, value: u64) -> Colour {
+        let index = (value as usize).min(self.colours.len() - 1);
+        return self.colours[index];
+    }
+}
+
+/// Set of cells drawn in a single colour over the top of a grid, such as a basin or a path.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Overlay {
+    cells: HashSet<(usize, usize)>,
+    colour: Colour,
+}
+
+impl Overlay {
+    /// Creates a new overlay highlighting the given (x, y) cells in the colour.
+    pub fn new<I: IntoIterator<Item = (usize, usize)>>(cells: I, colour: Colour) -> Self {
+        Self {
+            cells: cells.into_iter().collect::<HashSet<(usize, usize)>>(),
+            colour: colour,
+        }
+    }
+}
+
+/// Grid of coloured cells, ready to be written out as a PPM or SVG image.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GridImage {
+    width: usize,
+    height: usize,
+    cells: Vec<Colour>,
+}
+
+impl GridImage {
+    /// Creates an image from the grid (indexed by [y][x]), with each cell coloured by the given
+    /// function. Returns an error if the rows of the grid are not all the same length.
+    pub fn from_grid<T, F: Fn(&T) -> Colour>(
+        grid: &[Vec<T>],
+        colour_of: F,
+    ) -> Result<Self, String> {
+        let width = grid.first().map(|row| row.len()).unwrap_or(0);
+        if let Some(y) = grid.iter().position(|row| row.len() != width) {
+            return Err(format!(
+                "row {} has {} cells, expected {}",
+                y,
+                grid[y].len(),
+                width
+            ));
+        }
+        return Ok(Self {
+            width: width,
+            height: grid.len(),
+            cells: grid
+                .iter()
+                .flatten()
+                .map(colour_of)
+                .collect::<Vec<Colour>>(),
+        });
+    }
+
+    /// Creates an image from a grid of integer values, with each cell coloured from the palette.
+    pub fn from_values<T: Copy + Into<u64>>(
+        grid: &[Vec<T>],
+        palette: &Palette,
+    ) -> Result<Self, String> {
+        return GridImage::from_grid(grid, |value| palette.colour((*value).into()));
+    }
+
+    /// Gets the width of the image in cells.
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    /// Gets the height of the image in cells.
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    /// Gets the colour of the cell at (x, y). Returns None if the cell is outside of the image.
+    pub fn get(&self, x: usize, y: usize) -> Option<Colour> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        return Some(self.cells[y * self.width + x]);
+    }
+
+    /// Draws the overlay over the image. Cells of the overlay outside of the image are ignored.
+    /// Overlays applied later are drawn on top of those applied earlier.
+    pub fn apply_overlay(&mut self, overlay: &Overlay) {
+        for (x, y) in overlay.cells.iter() {
+            if *x < self.width && *y < self.height {
+                self.cells[y * self.width + x] = overlay.colour;
+            }
+        }
+    }
+
+    /// Renders the image in binary PPM (P6) format, with each cell drawn as a square of `scale`
+    /// pixels on each side. A scale of 0 is treated as 1.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let mut output =
+            format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+        for y in 0..self.height {
+            let mut row: Vec<u8> = vec![];
+            for colour in &self.cells[y * self.width..(y + 1) * self.width] {
+                for _ in 0..scale {
+                    row.extend([colour.r, colour.g, colour.b]);
+                }
+            }
+            for _ in 0..scale {
+                output.extend(&row);
+            }
+        }
+        return output;
+    }
+
+    /// Renders the image as an SVG document, with each cell drawn as a square of `scale` units on
+    /// each side. A scale of 0 is treated as 1. Runs of the same colour along a row are drawn as a
+    /// single rectangle to keep the document small.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let scale = scale.max(1);
+        let mut output = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             shape-rendering=\"crispEdges\">\n",
+            self.width * scale,
+            self.height * scale
+        );
+        for y in 0..self.height {
+            let row = &self.cells[y * self.width..(y + 1) * self.width];
+            let mut x = 0;
+            while x < self.width {
+                let run = row[x..].iter().take_while(|c| **c == row[x]).count();
+                output.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x * scale,
+                    y * scale,
+                    run * scale,
+                    scale,
+                    row[x]
+                ));
+                x += run;
+            }
+        }
+        output.push_str("</svg>\n");
+        return output;
+    }
+
+    /// Renders the image for display in a terminal that supports 24-bit ANSI colours, with each
+    /// cell drawn as two spaces on a coloured background. Each row ends by resetting the colour.
+    pub fn to_ansi(&self) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            let mut current: Option<Colour> = None;
+            for colour in &self.cells[y * self.width..(y + 1) * self.width] {
+                if current != Some(*colour) {
+                    output.push_str(&format!(
+                        "\x1b[48;2;{};{};{}m",
+                        colour.r, colour.g, colour.b
+                    ));
+                    current = Some(*colour);
+                }
+                output.push_str("  ");
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        return output;
+    }
+
+    /// Writes the image to the given file in binary PPM format.
+    pub fn write_ppm(&self, path: &str, scale: usize) -> io::Result<()> {
+        return fs::write(path, self.to_ppm(scale));
+    }
+
+    /// Writes the image to the given file as an SVG document.
+    pub fn write_svg(&self, path: &str, scale: usize) -> io::Result<()> {
+        return fs::write(path, self.to_svg(scale));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_palette() {
+        let palette = Palette::greyscale(10);
+        assert_eq!(Colour::BLACK, palette.colour(0));
+        assert_eq!(Colour::new(28, 28, 28), palette.colour(1));
+        assert_eq!(Colour::WHITE, palette.colour(9));
+        assert_eq!(Colour::WHITE, palette.colour(42));
+        assert_eq!(None, Palette::new(vec![]));
+        assert_eq!("#ff8000", Colour::new(255, 128, 0).to_string());
+    }
+
+    #[test]
+    fn test_render_ppm_with_overlay() {
+        let palette = Palette::new(vec![Colour::BLACK, Colour::WHITE]).unwrap();
+        let mut image = GridImage::from_values(&[vec![0u8, 1], vec![1, 0]], &palette).unwrap();
+        image.apply_overlay(&Overlay::new(vec![(1, 1), (5, 5)], Colour::RED));
+        assert_eq!(Some(Colour::RED), image.get(1, 1));
+        assert_eq!(None, image.get(2, 0));
+        let ppm = image.to_ppm(2);
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&header[..], &ppm[..header.len()]);
+        assert_eq!(header.len() + 4 * 4 * 3, ppm.len());
+        // Second row of pixels repeats the first row of cells
+        let pixels = &ppm[header.len()..];
+        assert_eq!(&pixels[0..12], &pixels[12..24]);
+        assert_eq!(
+            &[0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255],
+            &pixels[0..12]
+        );
+        assert_eq!(&[255, 0, 0], &pixels[pixels.len() - 3..]);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let grid = vec![vec![1u8, 1, 0]];
+        let palette = Palette::new(vec![Colour::BLACK, Colour::RED]).unwrap();
+        let image = GridImage::from_values(&grid, &palette).unwrap();
+        assert_eq!(
+            "\x1b[48;2;255;0;0m    \x1b[48;2;0;0;0m  \x1b[0m\n",
+            image.to_ansi()
+        );
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let grid = vec![vec!['#', '#', '.'], vec!['.', '.', '.']];
+        let image = GridImage::from_grid(&grid, |c| match c {
+            '#' => Colour::YELLOW,
+            _ => Colour::BLACK,
+        })
+        .unwrap();
+        let svg = image.to_svg(10);
+        assert!(svg.contains("width=\"30\" height=\"20\""));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffff00\"/>"));
+        assert!(
+            svg.contains("<rect x=\"0\" y=\"10\" width=\"30\" height=\"10\" fill=\"#000000\"/>")
+        );
+        assert_eq!(3, svg.matches("<rect").count());
+        assert!(GridImage::from_grid(&[vec![1], vec![]], |_| Colour::BLACK).is_err());
+    }
+}
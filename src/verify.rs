@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use regex::Regex;
+
+use super::runner::*;
+
+/// Path of the manifest of expected answers, relative to the repository root.
+pub const MANIFEST_PATH: &str = "./answers.toml";
+
+/// Directory holding the example fixtures, relative to the repository root.
+pub const FIXTURE_DIR: &str = "./input/2021/test";
+
+/// Expected answer for one part of a day's solution on a single input file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Expectation {
+    pub day: u8,
+    pub part: u8,
+    pub input: String,
+    pub expected: String,
+}
+
+/// Result of checking a day's solution against an expected answer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Pass,
+    Fail { actual: String },
+    Error(String),
+}
+
+/// Expectation checked by verify, along with the outcome of the check.
+pub struct Verification {
+    pub expectation: Expectation,
+    pub outcome: Outcome,
+}
+
+/// Reads the manifest of expected answers from the given file.
+pub fn load_manifest(path: &str) -> Result<Vec<Expectation>, String> {
+    let manifest =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    return parse_manifest(&manifest);
+}
+
+/// Parses the manifest of expected answers. Each `[[answer]]` entry gives the day, the input file
+/// and the expected answer for `part1` and/or `part2`, as either an integer or a string.
+pub fn parse_manifest(manifest: &str) -> Result<Vec<Expectation>, String> {
+    let value = manifest
+        .parse::<toml::Value>()
+        .map_err(|e| format!("invalid manifest: {}", e))?;
+    let entries = match value.get("answer") {
+        Some(toml::Value::Array(entries)) => entries.clone(),
+        Some(_) => return Err(String::from("`answer` must be an array of tables")),
+        None => vec![],
+    };
+    let mut expectations: Vec<Expectation> = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let context = format!("answer {}", i + 1);
+        let day = entry
+            .get("day")
+            .and_then(|day| day.as_integer())
+            .filter(|day| (1..=25).contains(day))
+            .ok_or_else(|| format!("{} - missing or invalid day", context))?
+            as u8;
+        let input = entry
+            .get("input")
+            .and_then(|input| input.as_str())
+            .ok_or_else(|| format!("{} - missing input", context))?;
+        expectations.extend(parse_part_answers(entry, day, input, &context)?);
+    }
+    return Ok(expectations);
+}
+
+/// Finds the example fixtures in the given directory, which are named `day_NN_test_NNN.txt`. The
+/// expected answers for each fixture are read from the `.toml` file of the same name, which gives
+/// the answer for `part1` and/or `part2`. Fixtures are returned in order of file name.
+pub fn discover_fixtures(dir: &str) -> Result<Vec<Expectation>, String> {
+    let fixture_regex = Regex::new(r"^day_(\d{2})_test_\d{3}\.txt$").unwrap();
+    let mut fixtures: Vec<(u8, String)> = vec![];
+    let entries = fs::read_dir(dir).map_err(|e| format!("failed to read {}: {}", dir, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("failed to read {}: {}", dir, e))?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if let Some(caps) = fixture_regex.captures(&file_name) {
+            let day = caps[1].parse::<u8>().unwrap();
+            fixtures.push((day, entry.path().to_string_lossy().to_string()));
+        }
+    }
+    fixtures.sort_by(|a, b| a.1.cmp(&b.1));
+    let mut expectations: Vec<Expectation> = vec![];
+    for (day, path) in fixtures {
+        let sidecar_path = Path::new(&path).with_extension("toml");
+        let sidecar = fs::read_to_string(&sidecar_path).map_err(|e| {
+            format!(
+                "no expected answers for {} ({}: {})",
+                path,
+                sidecar_path.display(),
+                e
+            )
+        })?;
+        let answers = sidecar
+            .parse::<toml::Value>()
+            .map_err(|e| format!("invalid answers in {}: {}", sidecar_path.display(), e))?;
+        expectations.extend(parse_part_answers(
+            &answers,
+            day,
+            &path,
+            &sidecar_path.display().to_string(),
+        )?);
+    }
+    return Ok(expectations);
+}
+
+/// Reads the expected answers given for `part1` and/or `part2` in the table, as either an integer
+/// or a string. Returns an error if neither part has an answer.
+fn parse_part_answers(
+    table: &toml::Value,
+    day: u8,
+    input: &str,
+    context: &str,
+) -> Result<Vec<Expectation>, String> {
+    let mut expectations: Vec<Expectation> = vec![];
+    for part in 1..=2 {
+        let expected = match table.get(format!("part{}", part)) {
+            Some(toml::Value::Integer(answer)) => answer.to_string(),
+            Some(toml::Value::String(answer)) => answer.clone(),
+            Some(_) => {
+                return Err(format!(
+                    "{} - part{} must be an integer or string",
+                    context, part
+                ))
+            }
+            None => continue,
+        };
+        expectations.push(Expectation {
+            day: day,
+            part: part,
+            input: input.to_string(),
+            expected: expected,
+        });
+    }
+    if expectations.is_empty() {
+        return Err(format!("{} - no part1 or part2 answer", context));
+    }
+    return Ok(expectations);
+}
+
+/// Runs the solutions against the expected answers. Each input file is parsed once for all of the
+/// parts expected from it, and the input files are checked in parallel.
+pub fn verify(expectations: &[Expectation]) -> Vec<Verification> {
+    let mut groups: BTreeMap<(u8, &str), Vec<&Expectation>> = BTreeMap::new();
+    for expectation in expectations {
+        groups
+            .entry((expectation.day, &expectation.input))
+            .or_default()
+            .push(expectation);
+    }
+    return thread::scope(|scope| {
+        let handles = groups
+            .into_iter()
+            .map(|((day, input), group)| scope.spawn(move || verify_input(day, input, &group)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<Verification>>()
+    });
+}
+
+/// Checks the expected answers for the given day and input file.
+fn verify_input(day: u8, path: &str, group: &[&Expectation]) -> Vec<Verification> {
+    let parts = group.iter().map(|e| e.part).collect::<Vec<u8>>();
+    let result = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path, e))
+        .and_then(|input| run_day(day, &input, &parts).map_err(|e| e.to_string()));
+    return group
+        .iter()
+        .map(|expectation| {
+            let outcome = match &result {
+                Ok(report) => match report.parts.iter().find(|p| p.part == expectation.part) {
+                    Some(p) if p.answer == expectation.expected => Outcome::Pass,
+                    Some(p) => Outcome::Fail {
+                        actual: p.answer.clone(),
+                    },
+                    None => Outcome::Error(format!("no answer for part {}", expectation.part)),
+                },
+                Err(message) => Outcome::Error(message.clone()),
+            };
+            Verification {
+                expectation: (*expectation).clone(),
+                outcome: outcome,
+            }
+        })
+        .collect::<Vec<Verification>>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Checks the expectations and panics with a list of those that did not pass.
+    fn assert_all_pass(expectations: &[Expectation]) {
+        let failures = verify(expectations)
+            .into_iter()
+            .filter(|v| v.outcome != Outcome::Pass)
+            .map(|v| {
+                format!(
+                    "day {} part {} ({}): expected {:?}, got {:?}",
+                    v.expectation.day,
+                    v.expectation.part,
+                    v.expectation.input,
+                    v.expectation.expected,
+                    v.outcome
+                )
+            })
+            .collect::<Vec<String>>();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_manifest_answers() {
+        assert_all_pass(&load_manifest(MANIFEST_PATH).unwrap());
+    }
+
+    #[test]
+    fn test_fixture_answers() {
+        let fixtures = discover_fixtures(FIXTURE_DIR).unwrap();
+        let days = fixtures.iter().map(|e| e.day).collect::<HashSet<u8>>();
+        assert!(days.contains(&5) && days.contains(&22));
+        assert_all_pass(&fixtures);
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = "[[answer]]\nday = 1\ninput = \"a.txt\"\npart2 = \"x\"\n\n\
+                        [[answer]]\nday = 2\ninput = \"b.txt\"\npart1 = 5\npart2 = 6\n";
+        let expectations = parse_manifest(manifest).unwrap();
+        assert_eq!(3, expectations.len());
+        assert_eq!(
+            Expectation {
+                day: 1,
+                part: 2,
+                input: String::from("a.txt"),
+                expected: String::from("x"),
+            },
+            expectations[0]
+        );
+        assert_eq!("6", expectations[2].expected);
+        assert!(parse_manifest("[[answer]]\nday = 26\ninput = \"a.txt\"\npart1 = 1\n").is_err());
+        assert!(parse_manifest("[[answer]]\nday = 1\ninput = \"a.txt\"\n").is_err());
+    }
+}
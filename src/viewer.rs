@@ -0,0 +1,382 @@
+use std::io;
+use std::io::Write;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+
+use super::simulation::Simulation;
+
+/// Clears the terminal and moves the cursor to the top-left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Keys accepted by the viewer, shown beneath each frame when reading single key presses.
+const KEY_HELP: &str = "[enter] step | r run/pause | <n>j jump n steps | q quit";
+
+/// Commands accepted by the viewer, shown beneath each frame when reading whole lines of input.
+const COMMAND_HELP: &str = "commands: [enter] step | r run/pause | j <n> jump n steps | q quit";
+
+/// Command given to the viewer, from a key press or a single line of input.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ViewerCommand {
+    /// Advance the simulation by a single step.
+    Step,
+    /// Start running the simulation continuously, or pause it if it is already running.
+    ToggleRun,
+    /// Advance the simulation by the given number of steps, drawing only the last.
+    Jump(usize),
+    /// Stop viewing the simulation.
+    Quit,
+}
+
+/// How commands are given to the viewer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InputMode {
+    /// Each key press is a command, used when reading from a terminal.
+    Keys,
+    /// Each line is a command, used when the input is not a terminal.
+    Lines,
+}
+
+/// Input received by the viewer - either a single key press or a whole line.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ViewerInput {
+    Key(char),
+    Line(String),
+}
+
+/// Parses a line of input as a viewer command. An empty line steps the simulation.
+pub fn parse_command(line: &str) -> Result<ViewerCommand, String> {
+    let mut words = line.split_whitespace();
+    let command = match (words.next(), words.next()) {
+        (None, _) | (Some("s"), None) => ViewerCommand::Step,
+        (Some("r"), None) | (Some("p"), None) => ViewerCommand::ToggleRun,
+        (Some("j"), Some(steps)) => match steps.parse::<usize>() {
+            Ok(steps) => ViewerCommand::Jump(steps),
+            Err(_) => return Err(format!("invalid number of steps: {}", steps)),
+        },
+        (Some("q"), None) => ViewerCommand::Quit,
+        _ => return Err(format!("unknown command: {}", line.trim())),
+    };
+    if words.next().is_some() {
+        return Err(format!("unknown command: {}", line.trim()));
+    }
+    return Ok(command);
+}
+
+/// Parses a key press as a viewer command. Digits are collected into the step count for the next
+/// jump (and backspace removes the last of them), so these keys give no command. Any other key
+/// clears the step count. Ctrl-C and Ctrl-D quit, since the terminal does not turn them into
+/// signals while single key presses are read.
+pub fn parse_key(key: char, count: &mut String) -> Option<Result<ViewerCommand, String>> {
+    match key {
+        '0'..='9' => {
+            count.push(key);
+            return None;
+        }
+        '\x08' | '\x7f' => {
+            count.pop();
+            return None;
+        }
+        _ => (),
+    }
+    let steps = std::mem::take(count);
+    let command = match key {
+        '\n' | '\r' | ' ' | 's' => ViewerCommand::Step,
+        'r' | 'p' => ViewerCommand::ToggleRun,
+        'j' => match steps.parse::<usize>() {
+            Ok(steps) => ViewerCommand::Jump(steps),
+            Err(_) => return Some(Err(String::from("type the number of steps before j"))),
+        },
+        'q' | '\x03' | '\x04' => ViewerCommand::Quit,
+        // Escape only clears the step count
+        '\x1b' => return None,
+        _ => return Some(Err(format!("unknown key: {}", key.escape_default()))),
+    };
+    return Some(Ok(command));
+}
+
+/// Shows a simulation in the terminal, advancing it as keys are pressed or lines of commands are
+/// entered. While running, the simulation steps once per interval until paused. Any input
+/// received during a jump stops the jump before it is handled.
+pub struct Viewer<'a, W: Write> {
+    simulation: &'a mut dyn Simulation,
+    output: W,
+    interval: Duration,
+    mode: InputMode,
+    step: usize,
+    running: bool,
+    count: String,
+    message: Option<String>,
+}
+
+impl<'a, W: Write> Viewer<'a, W> {
+    /// Creates a new viewer that draws the simulation to the output, stepping once per interval
+    /// while running. The input mode determines the help shown beneath each frame.
+    pub fn new(
+        simulation: &'a mut dyn Simulation,
+        output: W,
+        interval: Duration,
+        mode: InputMode,
+    ) -> Self {
+        Self {
+            simulation: simulation,
+            output: output,
+            interval: interval,
+            mode: mode,
+            step: 0,
+            running: false,
+            count: String::new(),
+            message: None,
+        }
+    }
+
+    /// Gets the number of steps taken so far.
+    pub fn step(&self) -> usize {
+        return self.step;
+    }
+
+    /// Draws the simulation and then handles each input received until the quit command is given
+    /// or the sender is dropped. Input that is not a valid command is reported beneath the frame.
+    pub fn run(&mut self, inputs: &Receiver<ViewerInput>) -> io::Result<()> {
+        let interval = self.interval;
+        return self.run_with_ticks(inputs, |inputs| inputs.recv_timeout(interval));
+    }
+
+    /// Runs the viewer as for `run`, with the given function waiting for the next input while the
+    /// simulation is running. The function returns a timeout error when the simulation is due to
+    /// take its next step, so that tests can step the simulation without waiting on a clock.
+    fn run_with_ticks<F>(&mut self, inputs: &Receiver<ViewerInput>, mut wait: F) -> io::Result<()>
+    where
+        F: FnMut(&Receiver<ViewerInput>) -> Result<ViewerInput, RecvTimeoutError>,
+    {
+        self.draw()?;
+        // Input received while jumping, to be handled before waiting for more
+        let mut pending: Option<ViewerInput> = None;
+        loop {
+            let input = if let Some(input) = pending.take() {
+                input
+            } else if self.running {
+                match wait(inputs) {
+                    Ok(input) => input,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.advance(1);
+                        self.draw()?;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            } else {
+                match inputs.recv() {
+                    Ok(input) => input,
+                    Err(_) => return Ok(()),
+                }
+            };
+            self.message = None;
+            let command = match input {
+                ViewerInput::Key(key) => parse_key(key, &mut self.count),
+                ViewerInput::Line(line) => Some(parse_command(&line)),
+            };
+            match command {
+                Some(Ok(ViewerCommand::Step)) => self.advance(1),
+                Some(Ok(ViewerCommand::ToggleRun)) => self.running = !self.running,
+                Some(Ok(ViewerCommand::Jump(steps))) => pending = self.jump(steps, inputs),
+                Some(Ok(ViewerCommand::Quit)) => return Ok(()),
+                Some(Err(message)) => self.message = Some(message),
+                // Redraw to show the step count typed so far
+                None => (),
+            }
+            self.draw()?;
+        }
+    }
+
+    /// Advances the simulation by up to the given number of steps, checking for new input before
+    /// each step. Returns the input that stopped the jump, if any was received.
+    fn jump(&mut self, steps: usize, inputs: &Receiver<ViewerInput>) -> Option<ViewerInput> {
+        for _ in 0..steps {
+            match inputs.try_recv() {
+                Ok(input) => return Some(input),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => self.advance(1),
+            }
+        }
+        return None;
+    }
+
+    /// Advances the simulation by the given number of steps.
+    fn advance(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.simulation.step();
+        }
+        self.step += steps;
+    }
+
+    /// Clears the terminal and draws the current state of the simulation, followed by a status line.
+    fn draw(&mut self) -> io::Result<()> {
+        let state = if self.running { "running" } else { "paused" };
+        let help = match self.mode {
+            InputMode::Keys => KEY_HELP,
+            InputMode::Lines => COMMAND_HELP,
+        };
+        writeln!(
+            self.output,
+            "{}{}Step {} ({}) - {}",
+            CLEAR_SCREEN,
+            self.simulation.snapshot().to_ansi(),
+            self.step,
+            state,
+            help
+        )?;
+        if !self.count.is_empty() {
+            writeln!(self.output, "jump {} steps: press j", self.count)?;
+        }
+        if let Some(message) = &self.message {
+            writeln!(self.output, "error: {}", message)?;
+        }
+        return self.output.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::*;
+    use std::sync::mpsc;
+
+    use crate::simulation::simulation_for_day;
+
+    #[test]
+    fn test_viewer_parse_command() {
+        assert_eq!(Ok(ViewerCommand::Step), parse_command(""));
+        assert_eq!(Ok(ViewerCommand::ToggleRun), parse_command(" r "));
+        assert_eq!(Ok(ViewerCommand::Jump(25)), parse_command("j 25"));
+        assert_eq!(Ok(ViewerCommand::Quit), parse_command("q"));
+        assert!(parse_command("j").is_err());
+        assert!(parse_command("j x").is_err());
+        assert!(parse_command("x").is_err());
+    }
+
+    #[test]
+    fn test_viewer_parse_key() {
+        let mut count = String::new();
+        assert_eq!(Some(Ok(ViewerCommand::Step)), parse_key('\n', &mut count));
+        assert_eq!(
+            Some(Ok(ViewerCommand::ToggleRun)),
+            parse_key('r', &mut count)
+        );
+        assert_eq!(None, parse_key('2', &mut count));
+        assert_eq!(None, parse_key('9', &mut count));
+        assert_eq!(None, parse_key('\x7f', &mut count));
+        assert_eq!(None, parse_key('5', &mut count));
+        assert_eq!(
+            Some(Ok(ViewerCommand::Jump(25))),
+            parse_key('j', &mut count)
+        );
+        assert!(count.is_empty());
+        assert!(parse_key('j', &mut count).unwrap().is_err());
+        assert_eq!(Some(Ok(ViewerCommand::Quit)), parse_key('\x03', &mut count));
+        assert_eq!(None, parse_key('3', &mut count));
+        assert!(parse_key('x', &mut count).unwrap().is_err());
+        assert!(count.is_empty());
+    }
+
+    #[test]
+    fn test_viewer_keys() {
+        let input = read_to_string("./input/2021/test/day_25_test_001.txt").unwrap();
+        let mut simulation = simulation_for_day(25, &input).unwrap();
+        let (sender, receiver) = mpsc::channel::<ViewerInput>();
+        for key in "\nxs10j".chars() {
+            sender.send(ViewerInput::Key(key)).unwrap();
+        }
+        drop(sender);
+        let mut output: Vec<u8> = vec![];
+        let mut viewer = Viewer::new(
+            &mut *simulation,
+            &mut output,
+            Duration::from_millis(1),
+            InputMode::Keys,
+        );
+        // Viewer stops once the sender is dropped and every key has been handled
+        viewer.run(&receiver).unwrap();
+        assert_eq!(12, viewer.step());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(7, output.matches(CLEAR_SCREEN).count());
+        assert!(output.contains(&format!("Step 2 (paused) - {}\n", KEY_HELP)));
+        assert!(output.contains("jump 10 steps: press j"));
+        assert!(output.contains("error: unknown key: x"));
+    }
+
+    #[test]
+    fn test_viewer_lines() {
+        let input = read_to_string("./input/2021/test/day_25_test_001.txt").unwrap();
+        let mut simulation = simulation_for_day(25, &input).unwrap();
+        let (sender, receiver) = mpsc::channel::<ViewerInput>();
+        for line in ["", "bad", "s", "j 10"] {
+            sender.send(ViewerInput::Line(line.to_string())).unwrap();
+        }
+        drop(sender);
+        let mut output: Vec<u8> = vec![];
+        let mut viewer = Viewer::new(
+            &mut *simulation,
+            &mut output,
+            Duration::from_millis(1),
+            InputMode::Lines,
+        );
+        viewer.run(&receiver).unwrap();
+        assert_eq!(12, viewer.step());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(5, output.matches(CLEAR_SCREEN).count());
+        assert!(output.contains(&format!("Step 2 (paused) - {}\n", COMMAND_HELP)));
+        assert!(output.contains("error: unknown command: bad"));
+    }
+
+    #[test]
+    fn test_viewer_jump_interrupted() {
+        let input = read_to_string("./input/2021/test/day_25_test_001.txt").unwrap();
+        let mut simulation = simulation_for_day(25, &input).unwrap();
+        let (sender, receiver) = mpsc::channel::<ViewerInput>();
+        for key in "1000000jqs".chars() {
+            sender.send(ViewerInput::Key(key)).unwrap();
+        }
+        let mut output: Vec<u8> = vec![];
+        let mut viewer = Viewer::new(
+            &mut *simulation,
+            &mut output,
+            Duration::from_millis(1),
+            InputMode::Keys,
+        );
+        viewer.run(&receiver).unwrap();
+        // Quit pressed during the jump stops it, and keys after quit are not handled
+        assert_eq!(0, viewer.step());
+    }
+
+    #[test]
+    fn test_viewer_run_and_pause() {
+        let input = read_to_string("./input/2021/test/day_20_test_001.txt").unwrap();
+        let mut simulation = simulation_for_day(20, &input).unwrap();
+        let (sender, receiver) = mpsc::channel::<ViewerInput>();
+        sender.send(ViewerInput::Key('r')).unwrap();
+        drop(sender);
+        let mut output: Vec<u8> = vec![];
+        let mut viewer = Viewer::new(
+            &mut *simulation,
+            &mut output,
+            Duration::from_millis(1),
+            InputMode::Keys,
+        );
+        // Simulation is due to step on each of the first three ticks, then is paused
+        let mut ticks = 0;
+        viewer
+            .run_with_ticks(&receiver, |_| {
+                ticks += 1;
+                if ticks <= 3 {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+                return Ok(ViewerInput::Key('p'));
+            })
+            .unwrap();
+        // Viewer stops once the sender is dropped while paused
+        assert_eq!(3, viewer.step());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Step 0 (running)"));
+        assert!(output.contains("Step 2 (running)"));
+        assert!(output.ends_with(&format!("Step 3 (paused) - {}\n", KEY_HELP)));
+    }
+}